@@ -0,0 +1,62 @@
+// Vec-per-monkey vs structure of arrays on a jungle with millions of items.
+//
+// cargo bench --bench arena
+//
+// The monkeys come from input.txt, their queues get filled up with pseudo random worry levels.
+
+use std::time::Instant;
+
+use djungle::{Arena, Djungle, Item, Monkey, NumT, Parsable};
+
+const ITEMS: usize = 2_000_000;
+const ROUNDS: usize = 20;
+
+fn crowded_jungle(input: &str) -> Vec<Monkey> {
+    let mut monkeys = Vec::<Monkey>::parse(input.to_string());
+    let monkey_count = monkeys.len();
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    for i in 0..ITEMS {
+        // xorshift, good enough for worry levels
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        monkeys[i % monkey_count].items.push(Item {
+            worry_level: (seed % 1_000_000) as NumT,
+        });
+    }
+    monkeys
+}
+
+fn main() {
+    let input = std::fs::read_to_string("input.txt").unwrap();
+
+    let mut djungle: Djungle<u64> = Djungle::lift(crowded_jungle(&input));
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        djungle.round();
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "djungle {:>10.2?} per {} rounds, {:>6.1} M inspections/s",
+        elapsed,
+        ROUNDS,
+        djungle.monkeys.iter().map(|m| m.inspections).sum::<NumT>() as f64
+            / elapsed.as_secs_f64()
+            / 1e6
+    );
+
+    let mut arena = Arena::from(Djungle::<u64>::lift(crowded_jungle(&input)));
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        arena.round();
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "arena   {:>10.2?} per {} rounds, {:>6.1} M inspections/s",
+        elapsed,
+        ROUNDS,
+        arena.inspections.iter().sum::<NumT>() as f64 / elapsed.as_secs_f64() / 1e6
+    );
+
+    assert_eq!(djungle.monkey_business(), arena.monkey_business());
+}
@@ -0,0 +1,50 @@
+// Compares the worry level representations on the real input.
+//
+// cargo bench --bench backends
+//
+// Every backend has to come up with the same monkey business, otherwise the timing is worthless.
+
+use std::time::{Duration, Instant};
+
+use djungle::{Barrett, Djungle, NumT, Parsable, Residues, Worry};
+
+const ROUNDS: usize = 10000;
+const RUNS: u32 = 5;
+
+fn bench<W: Worry>(name: &str, input: &str) -> NumT {
+    let mut total = Duration::ZERO;
+    let mut monkey_business = 0;
+    for _ in 0..RUNS {
+        let mut djungle: Djungle<W> = Djungle::parse(input.to_string());
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            djungle.round();
+        }
+        total += start.elapsed();
+        monkey_business = djungle.monkey_business();
+    }
+    println!(
+        "{:<10} {:>10.2?} per {} rounds ({})",
+        name,
+        total / RUNS,
+        ROUNDS,
+        monkey_business
+    );
+    monkey_business
+}
+
+fn main() {
+    let input = std::fs::read_to_string("input.txt").unwrap();
+    let expected = bench::<u128>("u128", &input);
+    let results = [
+        bench::<u64>("u64", &input),
+        bench::<Barrett>("barrett", &input),
+        bench::<Residues>("residues", &input),
+    ];
+    assert!(
+        results.iter().all(|r| *r == expected),
+        "backends disagree: {} vs {:?}",
+        expected,
+        results
+    );
+}
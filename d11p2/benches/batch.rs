@@ -0,0 +1,43 @@
+// Item by item vs lanes, for the worry types that can do lanes.
+//
+// cargo bench --bench batch
+
+use std::time::Instant;
+
+use djungle::{Djungle, Lane, NumT, Parsable, Relief};
+
+const RUNS: usize = 5;
+
+fn bench<W: Lane>(name: &str, input: &str, relief: Relief, rounds: usize) {
+    let mut results: [NumT; 2] = [0, 0];
+    for _ in 0..RUNS {
+        for (batched, result) in results.iter_mut().enumerate() {
+            let mut djungle: Djungle<W> = Djungle::parse(input.to_string()).with_relief(relief);
+            let start = Instant::now();
+            for _ in 0..rounds {
+                if batched == 1 {
+                    djungle.round_batched();
+                } else {
+                    djungle.round();
+                }
+            }
+            println!(
+                "{:<5} {:?} {:<8} {:>10.2?} per {} rounds",
+                name,
+                relief,
+                if batched == 1 { "batched" } else { "scalar" },
+                start.elapsed(),
+                rounds
+            );
+            *result = djungle.monkey_business();
+        }
+    }
+    assert_eq!(results[0], results[1]);
+}
+
+fn main() {
+    let input = std::fs::read_to_string("input.txt").unwrap();
+    bench::<u32>("u32", &input, Relief::DivideByThree, 20);
+    bench::<u64>("u64", &input, Relief::DivideByThree, 20);
+    bench::<u64>("u64", &input, Relief::None, 10000);
+}
@@ -0,0 +1,40 @@
+// Interpreted vs compiled monkeys on the real input.
+//
+// cargo bench --bench compiled
+
+use std::time::{Duration, Instant};
+
+use djungle::{CompiledDjungle, Djungle, NumT, Parsable, Worry};
+
+const ROUNDS: usize = 10000;
+const RUNS: u32 = 5;
+
+fn time(name: &str, mut round: impl FnMut()) {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        round();
+    }
+    let elapsed: Duration = start.elapsed();
+    println!("{:<24} {:>10.2?} per {} rounds", name, elapsed, ROUNDS);
+}
+
+fn bench<W: Worry + 'static>(name: &str, input: &str) {
+    let mut interpreted_business: NumT = 0;
+    let mut compiled_business: NumT = 0;
+    for _ in 0..RUNS {
+        let mut interpreted: Djungle<W> = Djungle::parse(input.to_string());
+        time(&format!("{} interpreted", name), || interpreted.round());
+        interpreted_business = interpreted.monkey_business();
+
+        let mut compiled = CompiledDjungle::new(Djungle::<W>::parse(input.to_string()));
+        time(&format!("{} compiled", name), || compiled.round());
+        compiled_business = compiled.monkey_business();
+    }
+    assert_eq!(interpreted_business, compiled_business);
+}
+
+fn main() {
+    let input = std::fs::read_to_string("input.txt").unwrap();
+    bench::<u64>("u64", &input);
+    bench::<u128>("u128", &input);
+}
@@ -0,0 +1,309 @@
+// Generates a simulator for one fixed input at compile time (used by the d11p2static binary).
+//
+// The jungle is parsed with the same code the library uses, then every monkey's turn is written out
+// with its operation and divisor as constants, so the compiler can turn `% 23` into a
+// multiplication and doesn't need to look anything up while simulating. Broken inputs end up as a
+// `compile_error!` in the generated file.
+//
+// Each part gets its own worry type. Part 2 is bounded by the mod factor, like `Backend::select`
+// says. Part 1 only has to fit for the rounds it's played: the overflow horizon proves that cheaply
+// if it can, but it assumes the worst about every test, so after a squaring monkey or two it gives
+// up on everything. Then we play those rounds with checked arithmetic and go by what happens.
+//
+// The input defaults to input.txt, `DJUNGLE_INPUT=test.txt cargo build` picks another one.
+//
+// Besides that, the tests get one test per fixture directory (see src/regression.rs), and test.txt
+// written down as code in every `FixtureStyle`, to check that what src/fixture.rs writes compiles
+// into the same jungle.
+
+use std::fmt::Write;
+
+// only the parser, the overflow analysis and the fixture writer of the library, so we parse and
+// size exactly like it
+#[allow(dead_code)]
+#[path = "src/fixture.rs"]
+mod fixture;
+#[allow(dead_code)]
+#[path = "src/horizon.rs"]
+mod horizon;
+#[allow(dead_code)]
+#[path = "src/monkey.rs"]
+mod monkey;
+
+use fixture::{fixture_code, FixtureStyle};
+use horizon::{overflow_horizon, Horizon};
+use monkey::{
+    mod_factor, worst_case, Monkey, NumT, Operation, OperationParameter, OperationType, Parsable,
+    Relief,
+};
+
+const PART1_ROUNDS: usize = 20;
+
+// the narrowest of u32, u64 and u128 that fits
+fn worry_type(fits: impl Fn(u32) -> bool) -> Option<&'static str> {
+    [(32, "u32"), (64, "u64"), (128, "u128")]
+        .into_iter()
+        .find(|(bits, _)| fits(*bits))
+        .map(|(_, name)| name)
+}
+
+// the largest worry level part 1 really gets to, if it fits into a u128
+fn part1_largest(monkeys: &[Monkey]) -> Option<NumT> {
+    let mut items = monkeys
+        .iter()
+        .map(|m| m.items.iter().map(|i| i.worry_level).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let mut largest = items.iter().flatten().copied().max().unwrap_or(0);
+    for _ in 0..PART1_ROUNDS {
+        for (id, monkey) in monkeys.iter().enumerate() {
+            for old in std::mem::take(&mut items[id]) {
+                let new = monkey.operation.worst_case(old)?;
+                largest = largest.max(new);
+                let new = new / 3;
+                let test = &monkey.test;
+                let target = match new % test.divisor {
+                    0 => test.true_target,
+                    _ => test.false_target,
+                };
+                items[target].push(new);
+            }
+        }
+    }
+    Some(largest)
+}
+
+fn fits(level: NumT, bits: u32) -> bool {
+    level <= NumT::MAX >> (NumT::BITS - bits)
+}
+
+fn operation(operation: &Operation) -> String {
+    let right = match operation.right {
+        OperationParameter::Constant(c) => c.to_string(),
+        OperationParameter::Old => "old".to_string(),
+    };
+    match operation.operation {
+        OperationType::Add => format!("old + {}", right),
+        OperationType::Multiply => format!("old * {}", right),
+    }
+}
+
+fn generate(monkeys: &[Monkey], source: &str) -> Result<String, String> {
+    if monkeys.is_empty() {
+        return Err("there are no monkeys".to_string());
+    }
+    let part1 = worry_type(|bits| {
+        !matches!(
+            overflow_horizon(monkeys, Relief::DivideByThree, bits, PART1_ROUNDS),
+            Horizon::Overflow(_)
+        )
+    })
+    .or_else(|| {
+        let largest = part1_largest(monkeys)?;
+        worry_type(|bits| fits(largest, bits))
+    })
+    .ok_or(format!(
+        "part 1 worry levels don't fit into a u128 within {} rounds",
+        PART1_ROUNDS
+    ))?;
+    let part2 = worry_type(|bits| worst_case(monkeys).is_some_and(|w| fits(w, bits)))
+        .ok_or("part 2 worry levels don't fit into a u128".to_string())?;
+    let mod_factor = mod_factor(monkeys).ok_or("the mod factor doesn't fit into a u128")?;
+
+    let mut code = String::new();
+    writeln!(
+        code,
+        "// Generated by build.rs from {}, don't edit.",
+        source
+    )
+    .unwrap();
+    writeln!(code).unwrap();
+    writeln!(code, "pub const MONKEYS: usize = {};", monkeys.len()).unwrap();
+    writeln!(code).unwrap();
+    writeln!(
+        code,
+        "/// Divides by three, the worry type only fits this many rounds."
+    )
+    .unwrap();
+    writeln!(code, "pub mod part1 {{").unwrap();
+    writeln!(code, "    use super::MONKEYS;").unwrap();
+    writeln!(code).unwrap();
+    writeln!(code, "    pub type Worry = {};", part1).unwrap();
+    writeln!(code, "    pub const ROUNDS: usize = {};", PART1_ROUNDS).unwrap();
+    writeln!(code).unwrap();
+    write_part(&mut code, monkeys, Relief::DivideByThree);
+    writeln!(code, "}}").unwrap();
+    writeln!(code).unwrap();
+    writeln!(
+        code,
+        "/// Reduces modulo the mod factor, for as many rounds as we like."
+    )
+    .unwrap();
+    writeln!(code, "pub mod part2 {{").unwrap();
+    writeln!(code, "    use super::MONKEYS;").unwrap();
+    writeln!(code).unwrap();
+    writeln!(code, "    pub type Worry = {};", part2).unwrap();
+    writeln!(code, "    pub const MOD_FACTOR: Worry = {};", mod_factor).unwrap();
+    writeln!(code).unwrap();
+    write_part(&mut code, monkeys, Relief::None);
+    writeln!(code, "}}").unwrap();
+    Ok(code)
+}
+
+// `starting_items` and `round` of one part, inside its module
+fn write_part(code: &mut String, monkeys: &[Monkey], relief: Relief) {
+    writeln!(
+        code,
+        "    pub fn starting_items() -> [Vec<Worry>; MONKEYS] {{"
+    )
+    .unwrap();
+    writeln!(code, "        [").unwrap();
+    for monkey in monkeys {
+        let items = monkey.items.iter().map(|i| i.worry_level.to_string());
+        writeln!(
+            code,
+            "            vec![{}],",
+            items.collect::<Vec<_>>().join(", ")
+        )
+        .unwrap();
+    }
+    writeln!(code, "        ]").unwrap();
+    writeln!(code, "    }}").unwrap();
+    writeln!(code).unwrap();
+
+    writeln!(
+        code,
+        "    pub fn round(items: &mut [Vec<Worry>; MONKEYS], inspections: &mut [u64; MONKEYS]) {{"
+    )
+    .unwrap();
+    for (id, monkey) in monkeys.iter().enumerate() {
+        let test = &monkey.test;
+        writeln!(
+            code,
+            "        // Monkey {}: new = {}, divisible by {} ? {} : {}",
+            id,
+            operation(&monkey.operation),
+            test.divisor,
+            test.true_target,
+            test.false_target
+        )
+        .unwrap();
+        writeln!(
+            code,
+            "        inspections[{}] += items[{}].len() as u64;",
+            id, id
+        )
+        .unwrap();
+        writeln!(
+            code,
+            "        for old in std::mem::take(&mut items[{}]) {{",
+            id
+        )
+        .unwrap();
+        match relief {
+            Relief::DivideByThree => {
+                writeln!(
+                    code,
+                    "            let new = ({}) / 3;",
+                    operation(&monkey.operation)
+                )
+                .unwrap();
+            }
+            Relief::None => {
+                writeln!(code, "            let old = old % MOD_FACTOR;").unwrap();
+                writeln!(
+                    code,
+                    "            let new = {};",
+                    operation(&monkey.operation)
+                )
+                .unwrap();
+            }
+        }
+        writeln!(
+            code,
+            "            items[if new.is_multiple_of({}) {{ {} }} else {{ {} }}].push(new);",
+            test.divisor, test.true_target, test.false_target
+        )
+        .unwrap();
+        writeln!(code, "        }}").unwrap();
+    }
+    writeln!(code, "    }}").unwrap();
+}
+
+// one test per fixture directory, they end up in the tests of src/regression.rs
+fn fixture_tests(fixtures: &std::path::Path) -> String {
+    let mut dirs = std::fs::read_dir(fixtures)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    dirs.sort();
+
+    let mut code = String::new();
+    for dir in dirs {
+        let name = dir
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_lowercase()
+            .replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+        writeln!(code, "#[test]").unwrap();
+        writeln!(code, "fn test_fixture_{}() {{", name).unwrap();
+        // relative to the crate, wherever it's built
+        writeln!(
+            code,
+            "    check_fixture(&std::path::Path::new(env!(\"CARGO_MANIFEST_DIR\")).join({:?})).unwrap();",
+            dir.display().to_string()
+        )
+        .unwrap();
+        writeln!(code, "}}").unwrap();
+    }
+    code
+}
+
+fn main() {
+    let source = std::env::var("DJUNGLE_INPUT").unwrap_or_else(|_| "input.txt".to_string());
+    println!("cargo:rerun-if-env-changed=DJUNGLE_INPUT");
+    println!("cargo:rerun-if-changed={}", source);
+    println!("cargo:rerun-if-changed=src/monkey.rs");
+    println!("cargo:rerun-if-changed=src/horizon.rs");
+    // lets the generated simulator's tests find the input again
+    println!(
+        "cargo:rustc-env=DJUNGLE_SOURCE={}",
+        std::path::Path::new(&source)
+            .canonicalize()
+            .unwrap_or_default()
+            .display()
+    );
+
+    let code = std::fs::read_to_string(&source)
+        .map_err(|e| e.to_string())
+        .and_then(|input| Vec::<Monkey>::try_parse(&input))
+        .and_then(|monkeys| generate(&monkeys, &source))
+        .unwrap_or_else(|e| format!("compile_error!({:?});\n", format!("{}: {}", source, e)));
+
+    let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("jungle.rs");
+    std::fs::write(out, code).unwrap();
+
+    println!("cargo:rerun-if-changed=fixtures");
+    let tests = fixture_tests(std::path::Path::new("fixtures"));
+    let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("fixture_tests.rs");
+    std::fs::write(out, tests).unwrap();
+
+    println!("cargo:rerun-if-changed=test.txt");
+    println!("cargo:rerun-if-changed=src/fixture.rs");
+    let monkeys = Vec::<Monkey>::parse(std::fs::read_to_string("test.txt").unwrap());
+    let code = [
+        FixtureStyle::Builder,
+        FixtureStyle::Macro,
+        FixtureStyle::Literals,
+    ]
+    .into_iter()
+    .map(|style| fixture_code(&monkeys, &format!("{}_fixture", style), style))
+    .collect::<String>();
+    let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("fixture_code.rs");
+    std::fs::write(out, code).unwrap();
+}
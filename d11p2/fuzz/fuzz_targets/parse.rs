@@ -0,0 +1,25 @@
+// Anything at all for the parsers
+//
+// Errors are fine, panics aren't, not even when the jungle doesn't fit the worry type. Whatever
+// parses has to be written back the same way it was read.
+
+#![no_main]
+
+use djungle::{to_input, Djungle, Monkey, Operation, Parsable, Test};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    let _ = Operation::try_parse(input);
+    let _ = Test::try_parse(input);
+    let _ = Djungle::<u64>::try_parse(input);
+    let _ = Djungle::<u128>::try_parse(input);
+    let Ok(monkeys) = Vec::<Monkey>::try_parse(input) else {
+        return;
+    };
+    let written = to_input(&monkeys);
+    let again = Vec::<Monkey>::try_parse(&written).expect("written monkeys parse again");
+    assert_eq!(to_input(&again), written);
+});
@@ -0,0 +1,47 @@
+// Playing whatever parses, in checked mode
+//
+// The jungle runs as long as the overflow horizon says a u128 can't overflow, with relief and
+// without, and `Checked` looks at every round. Inputs that would only test our patience (lots of
+// monkeys or items) or whose mod factor doesn't fit a u128 at all are skipped.
+
+#![no_main]
+
+use djungle::{
+    mod_factor, overflow_horizon, Checked, Djungle, Horizon, Monkey, NumT, Parsable, Relief,
+};
+use libfuzzer_sys::fuzz_target;
+
+const ROUNDS: usize = 200;
+const SHADOW_ROUNDS: usize = 5;
+const MAX_MONKEYS: usize = 16;
+const MAX_ITEMS: usize = 64;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Ok(monkeys) = Vec::<Monkey>::try_parse(input) else {
+        return;
+    };
+    let items = monkeys.iter().map(|m| m.items.len()).sum::<usize>();
+    if monkeys.len() > MAX_MONKEYS || items > MAX_ITEMS {
+        return;
+    }
+    if mod_factor(&monkeys).is_none() {
+        return;
+    }
+
+    for relief in [Relief::None, Relief::DivideByThree] {
+        let rounds = match overflow_horizon(&monkeys, relief, NumT::BITS, ROUNDS) {
+            Horizon::Overflow(round) => round - 1,
+            Horizon::Never | Horizon::Beyond(_) => ROUNDS,
+        };
+        let djungle: Djungle = Djungle::lift(monkeys.clone()).with_relief(relief);
+        let mut checked = Checked::new(djungle, SHADOW_ROUNDS);
+        for _ in 0..rounds {
+            if let Err(violation) = checked.round() {
+                panic!("{}", violation);
+            }
+        }
+    }
+});
@@ -0,0 +1,338 @@
+// Skipping deterministic chains of monkeys
+//
+// Modulo the mod factor every operation except `old * old` is an affine map `x -> a * x + b`, and
+// affine maps compose into affine maps. Usually we still have to look at the worry level after
+// every monkey to know where the item goes next, but not always: if `a` is divisible by the
+// divisor of a test, the outcome only depends on `b` (and if both targets are the same monkey it
+// doesn't depend on anything). So starting at a monkey we keep composing for as long as
+// * the next monkey's operation is affine,
+// * its test has a known outcome for the composed map,
+// * and it throws to a monkey after it, which still gets its turn in the same round.
+// An item inspected by the first monkey of such a chain jumps straight to where the chain ends
+// with one transform, every monkey along the way still counts its inspection. Squaring monkeys,
+// tests we can't predict and throws into the next round end the chain and things go on step by
+// step.
+//
+// Only works without relief, dividing by three isn't affine. The items arrive at the end of a
+// chain earlier than they would have, so the order of the queues may differ, the inspections don't.
+//
+// A composed map can multiply by anything below the mod factor, so applying it can get a lot larger
+// than any single operation does (what `Backend::select` looks at). Maps the backend can't apply
+// without overflowing end the chain as well.
+
+use crate::{
+    mod_factor, Backend, Djungle, Monkey, NumT, Operation, OperationParameter, OperationType,
+    Relief, Worry,
+};
+
+/// `x -> mul * x + add`, modulo the mod factor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Affine {
+    pub mul: NumT,
+    pub add: NumT,
+}
+
+impl Affine {
+    pub const IDENTITY: Affine = Affine { mul: 1, add: 0 };
+
+    /// First `self`, then `next`, or `None` if composing them doesn't fit into a u128.
+    pub fn then(&self, next: &Affine, mod_factor: NumT) -> Option<Affine> {
+        Some(Affine {
+            mul: next.mul.checked_mul(self.mul)? % mod_factor,
+            add: next.mul.checked_mul(self.add)?.checked_add(next.add)? % mod_factor,
+        })
+    }
+
+    /// Whether `apply` stays below `largest` for every worry level.
+    pub fn fits(&self, mod_factor: NumT, largest: NumT) -> bool {
+        let reduced = mod_factor.saturating_sub(1);
+        reduced.checked_mul(self.mul).is_some_and(|w| w <= largest)
+            && reduced.checked_add(self.add).is_some_and(|w| w <= largest)
+    }
+
+    /// Applies the map to a worry level. Intermediate values go up to `(mod_factor - 1)^2`.
+    pub fn apply<W: Worry>(&self, worry: &W, modulus: &W::Modulus) -> W {
+        let mut worry = worry.clone();
+        worry.reduce(modulus);
+        let mut worry = worry.mul_constant(self.mul, modulus);
+        worry.reduce(modulus);
+        worry.add_constant(self.add, modulus)
+    }
+}
+
+impl Operation {
+    pub fn as_affine(&self, mod_factor: NumT) -> Option<Affine> {
+        match (&self.operation, &self.right) {
+            (OperationType::Add, OperationParameter::Constant(c)) => Some(Affine {
+                mul: 1,
+                add: c % mod_factor,
+            }),
+            (OperationType::Add, OperationParameter::Old) => Some(Affine { mul: 2, add: 0 }),
+            (OperationType::Multiply, OperationParameter::Constant(c)) => Some(Affine {
+                mul: c % mod_factor,
+                add: 0,
+            }),
+            (OperationType::Multiply, OperationParameter::Old) => None,
+        }
+    }
+}
+
+/// A chain of monkeys an item can jump through in one step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Skip {
+    /// Everything the monkeys in the chain do to the worry level.
+    pub transform: Affine,
+    /// The monkeys that inspect the item on the way, starting with the first one.
+    pub visits: Vec<usize>,
+    /// Where the item ends up.
+    pub lands_at: usize,
+}
+
+fn known_target<W: Worry>(monkey: &Monkey<W>, transform: &Affine) -> Option<usize> {
+    let test = &monkey.test;
+    if test.true_target == test.false_target {
+        Some(test.true_target)
+    } else if !transform.mul.is_multiple_of(test.divisor) {
+        None
+    } else if transform.add.is_multiple_of(test.divisor) {
+        Some(test.true_target)
+    } else {
+        Some(test.false_target)
+    }
+}
+
+/// For every monkey, the chain starting there, if it has one that `backend` can apply.
+pub fn skips<W: Worry>(monkeys: &[Monkey<W>], backend: Backend) -> Vec<Option<Skip>> {
+    // nothing to compose without a mod factor
+    let Some(mod_factor) = mod_factor(monkeys) else {
+        return vec![None; monkeys.len()];
+    };
+    let fits = |transform: &Affine| match backend.largest() {
+        Some(largest) => transform.fits(mod_factor, largest),
+        None => true,
+    };
+    (0..monkeys.len())
+        .map(|start| {
+            let mut transform = monkeys[start]
+                .operation
+                .as_affine(mod_factor)
+                .filter(fits)?;
+            let mut visits = vec![start];
+            let mut target = known_target(&monkeys[start], &transform)?;
+            // keep going while the next monkey still gets its turn this round
+            while target > *visits.last().unwrap() {
+                let next = &monkeys[target];
+                let Some(composed) = next
+                    .operation
+                    .as_affine(mod_factor)
+                    .and_then(|a| transform.then(&a, mod_factor))
+                    .filter(fits)
+                else {
+                    break;
+                };
+                let Some(next_target) = known_target(next, &composed) else {
+                    break;
+                };
+                transform = composed;
+                visits.push(target);
+                target = next_target;
+            }
+            Some(Skip {
+                transform,
+                visits,
+                lands_at: target,
+            })
+        })
+        .collect()
+}
+
+impl<W: Worry> Djungle<W> {
+    /// Same as `round`, but items jump through the given chains in one step.
+    pub fn round_skipping(&mut self, skips: &[Option<Skip>]) {
+        assert_eq!(self.relief, Relief::None, "dividing by three isn't affine");
+        // after stepping, the round is finished from wherever we are
+        for (monkey_id, skip) in skips.iter().enumerate().skip(self.turn) {
+            let Some(skip) = skip else {
+                let throws =
+                    self.monkeys[monkey_id].inspect_and_throw(&self.mod_factor, self.relief);
+                for throw in throws {
+                    self.monkeys[throw.target].catch(throw.item);
+                }
+                continue;
+            };
+            let items = std::mem::take(&mut self.monkeys[monkey_id].items);
+            for visit in skip.visits.iter() {
+                self.monkeys[*visit].inspections += items.len() as NumT;
+            }
+            for mut item in items {
+                item.worry_level = skip.transform.apply(&item.worry_level, &self.mod_factor);
+                self.monkeys[skip.lands_at].catch(item);
+            }
+        }
+        self.turn = 0;
+        self.steps = 0;
+        self.played += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Parsable, Residues};
+
+    fn test_skips(input: &str) -> Vec<Option<Skip>> {
+        let monkeys = Vec::<Monkey>::parse(input.to_string());
+        skips(&monkeys, Backend::select(&monkeys).unwrap())
+    }
+
+    // monkey 0 multiplies by a multiple of its divisor, so it always throws to monkey 1, which
+    // always throws to monkey 2, which squares
+    const CHAIN: &str = "Monkey 0:
+  Starting items: 79, 98, 4
+  Operation: new = old * 6
+  Test: divisible by 3
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65
+  Operation: new = old + 4
+  Test: divisible by 5
+    If true: throw to monkey 2
+    If false: throw to monkey 2
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 7
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 11
+    If true: throw to monkey 0
+    If false: throw to monkey 1
+";
+
+    #[test]
+    fn test_compose() {
+        let m = 97;
+        let plus_three = Affine { mul: 1, add: 3 };
+        let times_five = Affine { mul: 5, add: 0 };
+        // (x + 3) * 5
+        assert_eq!(
+            plus_three.then(&times_five, m),
+            Some(Affine { mul: 5, add: 15 })
+        );
+        assert_eq!(
+            Affine::IDENTITY.then(&plus_three, m),
+            plus_three.then(&Affine::IDENTITY, m)
+        );
+        let composed = plus_three.then(&times_five, m).unwrap();
+        for x in 0..200u64 {
+            assert_eq!(
+                composed.apply(&x, &(m as u64)) % 97,
+                ((x % 97 + 3) * 5) % 97
+            );
+        }
+    }
+
+    #[test]
+    fn test_finds_chain() {
+        let skips = test_skips(CHAIN);
+        assert_eq!(
+            skips[0],
+            Some(Skip {
+                transform: Affine { mul: 6, add: 4 },
+                visits: vec![0, 1],
+                lands_at: 2
+            })
+        );
+        assert_eq!(skips[1].as_ref().map(|s| s.lands_at), Some(2));
+        assert_eq!(skips[2], None);
+        assert_eq!(skips[3], None);
+    }
+
+    #[test]
+    fn test_same_inspections_as_round() {
+        for input in [
+            CHAIN,
+            include_str!("../test.txt"),
+            include_str!("../input.txt"),
+        ] {
+            let mut stepwise: Djungle<u64> = Djungle::parse(input.to_string());
+            let mut skipping: Djungle<Residues> = Djungle::parse(input.to_string());
+            let skips = test_skips(input);
+            for _ in 0..10000 {
+                stepwise.round();
+                skipping.round_skipping(&skips);
+            }
+            for (s, k) in stepwise.monkeys.iter().zip(skipping.monkeys.iter()) {
+                assert_eq!(s.inspections, k.inspections);
+                assert_eq!(s.items.len(), k.items.len());
+            }
+            assert_eq!(skipping.monkey_business(), stepwise.monkey_business());
+        }
+    }
+
+    #[test]
+    fn test_round_after_stepping() {
+        let mut stepwise: Djungle<u64> = Djungle::parse(CHAIN.to_string());
+        // monkey 3 has thrown one item back to monkey 0 or 1, who must not inspect it again
+        for _ in 0..17 {
+            stepwise.step();
+        }
+        assert_eq!(stepwise.turn, 3);
+        let mut skipping = stepwise.clone();
+        stepwise.round();
+        skipping.round_skipping(&test_skips(CHAIN));
+        for (s, k) in stepwise.monkeys.iter().zip(skipping.monkeys.iter()) {
+            assert_eq!(s.inspections, k.inspections);
+            assert_eq!(s.items.len(), k.items.len());
+        }
+        assert_eq!((skipping.position(), skipping.turn), ((1, 0), 0));
+    }
+
+    #[test]
+    fn test_overflow() {
+        // with a mod factor above 2^64 composing overflows a u128
+        let big = 1 << 65;
+        let times_big = Affine {
+            mul: big - 1,
+            add: 0,
+        };
+        assert_eq!(times_big.then(&times_big, 1 << 127), None);
+        assert!(!times_big.fits(big, NumT::MAX));
+
+        // without a mod factor there's nothing to skip
+        let monkeys = Djungle::<Residues>::builder()
+            .monkey(|m| m.items([1]).add(1).divisible_by(big).throw(1, 1))
+            .monkey(|m| m.items([1]).add(1).divisible_by(big).throw(0, 0))
+            .build_monkeys()
+            .unwrap();
+        assert_eq!(skips(&monkeys, Backend::Residues), vec![None, None]);
+    }
+
+    #[test]
+    fn test_chain_fits_backend() {
+        // no operation goes beyond a u32, times 6 * 1000 (which is what skipping 0 and 1 does)
+        // does
+        let monkeys = Djungle::<u32>::builder()
+            .monkey(|m| m.items([1]).mul(6).divisible_by(3).throw(1, 1))
+            .monkey(|m| m.items([1]).mul(1000).divisible_by(100003).throw(2, 2))
+            .monkey(|m| m.items([1]).add(1).divisible_by(7).throw(0, 1))
+            .build_monkeys()
+            .unwrap();
+        assert_eq!(Backend::select(&monkeys), Ok(Backend::U32));
+        let lands_at = |backend| {
+            skips(&monkeys, backend)
+                .iter()
+                .map(|s| s.as_ref().map(|s| s.lands_at))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(lands_at(Backend::U32), vec![Some(1), Some(2), None]);
+        assert_eq!(lands_at(Backend::U64), vec![Some(2), Some(2), None]);
+    }
+}
@@ -0,0 +1,198 @@
+// Structure of arrays item storage
+//
+// In a `Djungle` every monkey owns a `Vec` of items and every throw moves an item from one vector
+// to another. Here all items live in one arena instead, in parallel arrays:
+// * `worry`: the worry level of every item
+// * `holder`: which monkey currently has it
+// * `order`: when it was caught, so we know the order of a monkey's queue
+//
+// At the start of a round the arena is sorted by holder, so the items a monkey starts its turn with
+// are one contiguous slice (nobody else can throw them away before its turn). Only items it caught
+// from monkeys before it in the same round are somewhere else, those are remembered in `caught`.
+// At the end of the round every item has been thrown at least once, so we sort the arena by holder
+// again, keeping the order in which the items were caught.
+
+use crate::{Djungle, NumT, Operation, Relief, Test, Worry};
+
+pub struct Arena<W: Worry = NumT> {
+    pub worry: Vec<W>,
+    pub holder: Vec<u32>,
+    pub order: Vec<u64>,
+    pub inspections: Vec<NumT>,
+    operations: Vec<Operation>,
+    tests: Vec<Test>,
+    mod_factor: W::Modulus,
+    relief: Relief,
+    // where every monkey's slice starts, one more entry for the end of the last one
+    slices: Vec<usize>,
+    // items caught during this round by monkeys that haven't had their turn yet
+    caught: Vec<Vec<u32>>,
+    next_order: u64,
+}
+
+impl<W: Worry> From<Djungle<W>> for Arena<W> {
+    fn from(djungle: Djungle<W>) -> Self {
+        let mut arena = Arena {
+            worry: Vec::new(),
+            holder: Vec::new(),
+            order: Vec::new(),
+            inspections: Vec::new(),
+            operations: Vec::new(),
+            tests: Vec::new(),
+            mod_factor: djungle.mod_factor,
+            relief: djungle.relief,
+            slices: vec![0],
+            caught: Vec::new(),
+            next_order: 0,
+        };
+        for (id, monkey) in djungle.monkeys.into_iter().enumerate() {
+            for item in monkey.items {
+                arena.worry.push(item.worry_level);
+                arena.holder.push(id as u32);
+                arena.order.push(arena.next_order);
+                arena.next_order += 1;
+            }
+            arena.slices.push(arena.worry.len());
+            arena.caught.push(Vec::new());
+            arena.inspections.push(monkey.inspections);
+            arena.operations.push(monkey.operation);
+            arena.tests.push(monkey.test);
+        }
+        arena
+    }
+}
+
+impl<W: Worry> Arena<W> {
+    /// The worry levels of the items a monkey holds, in the order it will inspect them.
+    pub fn items(&self, monkey: usize) -> Vec<&W> {
+        self.worry[self.slices[monkey]..self.slices[monkey + 1]]
+            .iter()
+            .collect()
+    }
+
+    pub fn round(&mut self) {
+        for monkey in 0..self.operations.len() {
+            for i in self.slices[monkey]..self.slices[monkey + 1] {
+                self.inspect(monkey, i);
+            }
+            let caught = std::mem::take(&mut self.caught[monkey]);
+            for i in caught.iter() {
+                self.inspect(monkey, *i as usize);
+            }
+            self.inspections[monkey] +=
+                (self.slices[monkey + 1] - self.slices[monkey] + caught.len()) as NumT;
+            // hand the allocation back
+            self.caught[monkey] = caught;
+            self.caught[monkey].clear();
+        }
+        self.sort_by_holder();
+    }
+
+    fn inspect(&mut self, monkey: usize, i: usize) {
+        let operation = &self.operations[monkey];
+        let test = &self.tests[monkey];
+        let worry = &mut self.worry[i];
+        match self.relief {
+            Relief::DivideByThree => {
+                *worry = operation.evaluate(worry, &self.mod_factor);
+                worry.divide_by_three();
+            }
+            Relief::None => {
+                worry.reduce(&self.mod_factor);
+                *worry = operation.evaluate(worry, &self.mod_factor);
+            }
+        }
+        let target = if worry.is_divisible_by(test.divisor, &self.mod_factor) {
+            test.true_target
+        } else {
+            test.false_target
+        };
+        self.holder[i] = target as u32;
+        self.order[i] = self.next_order;
+        self.next_order += 1;
+        // monkeys before this one (or itself) only get to it next round
+        if target > monkey {
+            self.caught[target].push(i as u32);
+        }
+    }
+
+    // counting sort by holder, stable by the order the items were caught in
+    fn sort_by_holder(&mut self) {
+        // every item got a new order this round, all of them at or above the number of items
+        let first_order = self.worry.len() as u64;
+        let mut by_order = vec![usize::MAX; (self.next_order - first_order) as usize];
+        for (i, order) in self.order.iter().enumerate() {
+            by_order[(order - first_order) as usize] = i;
+        }
+
+        let mut slices = vec![0; self.operations.len() + 1];
+        for holder in self.holder.iter() {
+            slices[*holder as usize + 1] += 1;
+        }
+        for monkey in 0..self.operations.len() {
+            slices[monkey + 1] += slices[monkey];
+        }
+
+        let mut next = slices.clone();
+        let mut sorted = vec![0; self.worry.len()];
+        // orders that were overwritten by a later throw of the same item are still usize::MAX
+        for i in by_order.into_iter().filter(|i| *i != usize::MAX) {
+            let holder = self.holder[i] as usize;
+            sorted[next[holder]] = i;
+            next[holder] += 1;
+        }
+
+        self.worry = sorted.iter().map(|i| self.worry[*i].clone()).collect();
+        self.holder = sorted.iter().map(|i| self.holder[*i]).collect();
+        self.order = (0..sorted.len() as u64).collect();
+        self.next_order = sorted.len() as u64;
+        self.slices = slices;
+    }
+
+    pub fn monkey_business(&self) -> NumT {
+        let mut inspections = self.inspections.clone();
+        inspections.sort_unstable();
+        inspections.iter().rev().take(2).product()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_same_as_plain;
+    use crate::Parsable;
+
+    #[test]
+    fn test_same_items_as_djungle() {
+        for relief in [Relief::DivideByThree, Relief::None] {
+            assert_same_as_plain::<u64, _>(
+                relief,
+                20,
+                Arena::from,
+                Arena::round,
+                |djungle, arena| {
+                    for (id, monkey) in djungle.monkeys.iter().enumerate() {
+                        let expected = monkey
+                            .items
+                            .iter()
+                            .map(|i| &i.worry_level)
+                            .collect::<Vec<_>>();
+                        assert_eq!(arena.items(id), expected);
+                        assert_eq!(arena.inspections[id], monkey.inspections);
+                    }
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn test_monkey_business() {
+        let mut arena = Arena::from(Djungle::<u64>::parse(
+            include_str!("../test.txt").to_string(),
+        ));
+        for _ in 0..10000 {
+            arena.round();
+        }
+        assert_eq!(arena.monkey_business(), 2713310158)
+    }
+}
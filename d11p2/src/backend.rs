@@ -0,0 +1,190 @@
+// Picking the narrowest worry type that can't overflow
+//
+// Before every operation the worry level is reduced below the mod factor, so the largest number we
+// ever have to store (`worst_case`, in monkey.rs) is the biggest of
+// * the mod factor itself,
+// * the largest starting item (it's only reduced on its first inspection),
+// * the largest result of any operation applied to `mod_factor - 1`.
+// If that fits into a u32 we use a u32, and so on. If it doesn't even fit into a u128, residue
+// vectors can't overflow either, but only as long as every divisor fits into a u32. A jungle with a
+// bigger divisor than that has no backend at all.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{worst_case, Djungle, Monkey, NumT, Residues, Worry};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    U32,
+    U64,
+    U128,
+    Residues,
+}
+
+/// Something that can run on any worry representation, so `Backend::dispatch` can pick one.
+pub trait Simulation {
+    type Output;
+
+    fn run<W: Worry>(self, djungle: Djungle<W>) -> Self::Output;
+}
+
+impl Backend {
+    /// The narrowest backend that can hold the jungle, or why none can.
+    pub fn select(monkeys: &[Monkey]) -> Result<Backend, String> {
+        match worst_case(monkeys) {
+            Some(w) if w <= u32::MAX as NumT => Ok(Backend::U32),
+            Some(w) if w <= u64::MAX as NumT => Ok(Backend::U64),
+            Some(_) => Ok(Backend::U128),
+            None => {
+                let divisors = monkeys.iter().map(|m| m.test.divisor).collect::<Vec<_>>();
+                Residues::modulus(&divisors)
+                    .map(|_| Backend::Residues)
+                    .map_err(|e| format!("worry levels don't fit into a u128, and {}", e))
+            }
+        }
+    }
+
+    /// The largest worry level the backend can hold, `None` if it can't overflow.
+    pub fn largest(self) -> Option<NumT> {
+        match self {
+            Backend::U32 => Some(u32::MAX as NumT),
+            Backend::U64 => Some(u64::MAX as NumT),
+            Backend::U128 => Some(u128::MAX),
+            Backend::Residues => None,
+        }
+    }
+
+    /// Runs the simulation on this backend, unless it can't hold the jungle.
+    pub fn dispatch<S: Simulation>(
+        self,
+        monkeys: Vec<Monkey>,
+        simulation: S,
+    ) -> Result<S::Output, String> {
+        Ok(match self {
+            Backend::U32 => simulation.run(Djungle::<u32>::try_lift(monkeys)?),
+            Backend::U64 => simulation.run(Djungle::<u64>::try_lift(monkeys)?),
+            Backend::U128 => simulation.run(Djungle::<u128>::try_lift(monkeys)?),
+            Backend::Residues => simulation.run(Djungle::<Residues>::try_lift(monkeys)?),
+        })
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Backend::U32 => "u32",
+            Backend::U64 => "u64",
+            Backend::U128 => "u128",
+            Backend::Residues => "residues",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "u32" => Ok(Backend::U32),
+            "u64" => Ok(Backend::U64),
+            "u128" => Ok(Backend::U128),
+            "residues" => Ok(Backend::Residues),
+            _ => Err(format!("unknown backend: {}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mod_factor, Item, Operation, OperationParameter, OperationType, Parsable, Test};
+
+    fn monkey(items: &[NumT], operation: OperationType, right: OperationParameter) -> Monkey {
+        Monkey {
+            id: 0,
+            items: items.iter().map(|i| Item { worry_level: *i }).collect(),
+            inspections: 0,
+            operation: Operation { operation, right },
+            test: Test {
+                divisor: 7,
+                true_target: 0,
+                false_target: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_select_for_inputs() {
+        let test = Vec::<Monkey>::parse(include_str!("../test.txt").to_string());
+        // mod factor 96577, squared that's more than a u32
+        assert_eq!(mod_factor(&test), Some(96577));
+        assert_eq!(worst_case(&test), Some(96576 * 96576));
+        assert_eq!(Backend::select(&test), Ok(Backend::U64));
+        let input = Vec::<Monkey>::parse(include_str!("../input.txt").to_string());
+        assert_eq!(Backend::select(&input), Ok(Backend::U64));
+    }
+
+    #[test]
+    fn test_select_narrowest() {
+        let small = vec![monkey(
+            &[3],
+            OperationType::Multiply,
+            OperationParameter::Old,
+        )];
+        assert_eq!(worst_case(&small), Some(36));
+        assert_eq!(Backend::select(&small), Ok(Backend::U32));
+
+        let big_start = vec![monkey(
+            &[u64::MAX as NumT],
+            OperationType::Add,
+            OperationParameter::Constant(1),
+        )];
+        assert_eq!(Backend::select(&big_start), Ok(Backend::U64));
+
+        let big_constant = vec![monkey(
+            &[1],
+            OperationType::Multiply,
+            OperationParameter::Constant(NumT::MAX / 2),
+        )];
+        assert_eq!(Backend::select(&big_constant), Ok(Backend::Residues));
+
+        // too big for a u128 and for residues
+        let mut big_divisors = vec![small[0].clone(), small[0].clone()];
+        big_divisors[0].test.divisor = 1 << 70;
+        big_divisors[1].test.divisor = 1 << 70;
+        assert_eq!(mod_factor(&big_divisors), None);
+        assert_eq!(
+            Backend::select(&big_divisors),
+            Err(format!(
+                "worry levels don't fit into a u128, and divisor {} does not fit into a u32",
+                1u128 << 70
+            ))
+        );
+    }
+
+    struct Business;
+
+    impl Simulation for Business {
+        type Output = NumT;
+
+        fn run<W: Worry>(self, mut djungle: Djungle<W>) -> NumT {
+            djungle.round();
+            djungle.monkey_business()
+        }
+    }
+
+    #[test]
+    fn test_dispatch_too_small() {
+        let test = Vec::<Monkey>::parse(include_str!("../test.txt").to_string());
+        assert!(Backend::U64.dispatch(test.clone(), Business).is_ok());
+        let mut big = test;
+        big[0].test.divisor = 1 << 70;
+        assert_eq!(
+            Backend::U64.dispatch(big.clone(), Business),
+            Err("mod factor does not fit into a u64".to_string())
+        );
+        assert!(Backend::Residues.dispatch(big, Business).is_err());
+    }
+}
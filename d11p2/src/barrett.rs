@@ -0,0 +1,146 @@
+// Barrett reduced worry levels
+//
+// `% mod_factor` on a u128 is a call into a slow software division on 64-bit hardware. The mod
+// factor never changes though, so we can precompute `r = floor((2^64 - 1) / m)` once and replace
+// every division by a multiplication and a shift:
+//
+//   q = (x * r) >> 64      (q is at most 2 below floor(x / m))
+//   x mod m = x - q * m    (minus m at most twice)
+//
+// Worry levels are always kept below the mod factor, which has to fit into a u32. That way the
+// product of two worry levels still fits into a u64 and nothing ever gets wider than that.
+
+use crate::{NumT, Worry};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarrettModulus {
+    m: u64,
+    r: u64,
+}
+
+impl BarrettModulus {
+    pub fn new(m: u32) -> Self {
+        assert!(m > 0, "mod factor must not be zero");
+        BarrettModulus {
+            m: m as u64,
+            r: u64::MAX / m as u64,
+        }
+    }
+
+    pub fn mod_factor(&self) -> u64 {
+        self.m
+    }
+
+    fn reduce(&self, x: u64) -> u64 {
+        let q = ((x as u128 * self.r as u128) >> 64) as u64;
+        let mut t = x - q * self.m;
+        while t >= self.m {
+            t -= self.m;
+        }
+        t
+    }
+
+    // constants in the input are tiny, only reduce them the slow way if they have to be
+    fn constant(&self, c: NumT) -> u64 {
+        match u32::try_from(c) {
+            Ok(c) => c as u64,
+            Err(_) => (c % self.m as NumT) as u64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Barrett(u64);
+
+impl Barrett {
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Worry for Barrett {
+    type Modulus = BarrettModulus;
+
+    fn modulus(divisors: &[NumT]) -> Result<BarrettModulus, String> {
+        divisors
+            .iter()
+            .try_fold(1u32, |acc, d| {
+                u32::try_from(*d).ok().and_then(|d| acc.checked_mul(d))
+            })
+            .map(BarrettModulus::new)
+            .ok_or_else(|| "mod factor does not fit into a u32".to_string())
+    }
+
+    fn try_lift(level: NumT, modulus: &BarrettModulus) -> Result<Self, String> {
+        Ok(Barrett((level % modulus.m as NumT) as u64))
+    }
+
+    fn reduce(&mut self, _: &BarrettModulus) {
+        // every operation already reduces
+    }
+
+    fn divide_by_three(&mut self) {
+        unreachable!("Barrett reduced worry levels can't be divided by three")
+    }
+
+    fn can_divide_by_three() -> Result<(), String> {
+        Err("Barrett reduced worry levels can't be divided by three".to_string())
+    }
+
+    fn add(&self, right: &Self, modulus: &BarrettModulus) -> Self {
+        Barrett(modulus.reduce(self.0 + right.0))
+    }
+
+    fn mul(&self, right: &Self, modulus: &BarrettModulus) -> Self {
+        Barrett(modulus.reduce(self.0 * right.0))
+    }
+
+    fn add_constant(&self, right: NumT, modulus: &BarrettModulus) -> Self {
+        Barrett(modulus.reduce(self.0 + modulus.constant(right)))
+    }
+
+    fn mul_constant(&self, right: NumT, modulus: &BarrettModulus) -> Self {
+        Barrett(modulus.reduce(self.0 * modulus.constant(right)))
+    }
+
+    fn is_divisible_by(&self, divisor: NumT, _: &BarrettModulus) -> bool {
+        self.0.is_multiple_of(divisor as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Djungle, Parsable};
+
+    #[test]
+    fn test_reduce_matches_modulo() {
+        for m in [1, 2, 3, 96577, 9699690, u32::MAX] {
+            let modulus = BarrettModulus::new(m);
+            for x in [0, 1, m as u64 - 1, m as u64, 12345678901234, u64::MAX] {
+                assert_eq!(modulus.reduce(x), x % m as u64, "{} mod {}", x, m);
+            }
+        }
+    }
+
+    #[test]
+    fn test_same_answer_as_u128() {
+        let input = include_str!("../input.txt").to_string();
+        let mut plain: Djungle = Djungle::parse(input.clone());
+        let mut barrett: Djungle<Barrett> = Djungle::parse(input);
+        for _ in 0..1000 {
+            plain.round();
+            barrett.round();
+        }
+        assert_eq!(barrett.monkey_business(), plain.monkey_business());
+    }
+
+    #[test]
+    fn test_monkey_business() {
+        let mut dj: Djungle<Barrett> = Djungle::parse(include_str!("../test.txt").to_string());
+        for _ in 0..10000 {
+            dj.round();
+        }
+        assert_eq!(dj.monkey_business(), 2713310158)
+    }
+}
@@ -0,0 +1,185 @@
+// Batched inspections for plain integer worry levels
+//
+// A monkey does the same thing to every item it holds, so instead of going through the `Worry`
+// trait one item at a time we copy its queue into fixed size chunks (lanes) and do every step for
+// the whole chunk at once: reduce, operation, relief, test. The operation is only matched once per
+// chunk and the loops over the lanes are simple enough for the compiler to vectorise where the
+// hardware can. Afterwards the chunk is split into the items for the true and the false target,
+// keeping their order, so the result is exactly what `inspect_and_throw` would have thrown.
+
+use std::ops::{Add, Div, Mul, Rem};
+
+use crate::{Djungle, Item, Monkey, NumT, OperationParameter, OperationType, Relief, Worry};
+
+const LANES: usize = 8;
+
+/// Plain integers that can be inspected in lanes.
+pub trait Lane:
+    Worry<Modulus = Self>
+    + Copy
+    + Default
+    + Add<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+{
+}
+
+impl Lane for u32 {}
+impl Lane for u64 {}
+
+// the operation with its constant already converted
+enum LaneOperation<T> {
+    Add(T),
+    Double,
+    Multiply(T),
+    Square,
+}
+
+/// The items one monkey throws in a turn, split by target.
+pub struct Batch<W: Worry> {
+    pub true_items: Vec<Item<W>>,
+    pub false_items: Vec<Item<W>>,
+}
+
+impl<W: Lane> Monkey<W> {
+    pub fn inspect_and_throw_batched(&mut self, mod_factor: &W, relief: Relief) -> Batch<W> {
+        let operation = match (&self.operation.operation, &self.operation.right) {
+            (OperationType::Add, OperationParameter::Constant(c)) => {
+                LaneOperation::Add(W::lift(*c, mod_factor))
+            }
+            (OperationType::Add, OperationParameter::Old) => LaneOperation::Double,
+            (OperationType::Multiply, OperationParameter::Constant(c)) => {
+                LaneOperation::Multiply(W::lift(*c, mod_factor))
+            }
+            (OperationType::Multiply, OperationParameter::Old) => LaneOperation::Square,
+        };
+        let divisor = W::lift(self.test.divisor, mod_factor);
+        let three = W::lift(3, mod_factor);
+        let zero = W::default();
+        // if both targets are the same monkey, splitting would mess up the order
+        let split = self.test.true_target != self.test.false_target;
+
+        let mut batch = Batch {
+            true_items: Vec::with_capacity(self.items.len()),
+            false_items: Vec::new(),
+        };
+        self.inspections += self.items.len() as NumT;
+        for chunk in self.items.chunks(LANES) {
+            let mut lanes = [zero; LANES];
+            for (lane, item) in lanes.iter_mut().zip(chunk) {
+                *lane = item.worry_level;
+            }
+
+            if relief == Relief::None {
+                lanes.iter_mut().for_each(|l| *l = *l % *mod_factor);
+            }
+            match operation {
+                LaneOperation::Add(c) => lanes.iter_mut().for_each(|l| *l = *l + c),
+                LaneOperation::Double => lanes.iter_mut().for_each(|l| *l = *l + *l),
+                LaneOperation::Multiply(c) => lanes.iter_mut().for_each(|l| *l = *l * c),
+                LaneOperation::Square => lanes.iter_mut().for_each(|l| *l = *l * *l),
+            }
+            if relief == Relief::DivideByThree {
+                lanes.iter_mut().for_each(|l| *l = *l / three);
+            }
+            let mut divisible = [false; LANES];
+            for (d, l) in divisible.iter_mut().zip(lanes.iter()) {
+                *d = *l % divisor == zero;
+            }
+
+            for i in 0..chunk.len() {
+                let item = Item {
+                    worry_level: lanes[i],
+                };
+                if divisible[i] || !split {
+                    batch.true_items.push(item);
+                } else {
+                    batch.false_items.push(item);
+                }
+            }
+        }
+        self.items.clear();
+        batch
+    }
+}
+
+impl<W: Lane> Djungle<W> {
+    /// Same as `round`, with every monkey inspecting its items in lanes.
+    pub fn round_batched(&mut self) {
+        // after stepping, the round is finished from wherever we are
+        for monkey_id in self.turn..self.monkeys.len() {
+            let monkey = &mut self.monkeys[monkey_id];
+            let batch = monkey.inspect_and_throw_batched(&self.mod_factor, self.relief);
+            let (true_target, false_target) = (monkey.test.true_target, monkey.test.false_target);
+            self.monkeys[true_target].items.extend(batch.true_items);
+            self.monkeys[false_target].items.extend(batch.false_items);
+        }
+        self.turn = 0;
+        self.steps = 0;
+        self.played += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{assert_same_as_plain, assert_same_monkeys};
+    use crate::{Operation, Parsable, Test};
+
+    fn assert_same_rounds<W: Lane>(relief: Relief, rounds: usize) {
+        assert_same_as_plain::<W, _>(
+            relief,
+            rounds,
+            |djungle| djungle,
+            Djungle::round_batched,
+            |scalar, batched| assert_same_monkeys(&scalar.monkeys, &batched.monkeys),
+        );
+    }
+
+    #[test]
+    fn test_same_as_scalar() {
+        assert_same_rounds::<u32>(Relief::DivideByThree, 20);
+        assert_same_rounds::<u64>(Relief::DivideByThree, 20);
+        assert_same_rounds::<u64>(Relief::None, 1000);
+    }
+
+    #[test]
+    fn test_round_after_stepping() {
+        let mut scalar: Djungle<u64> = Djungle::parse(include_str!("../test.txt").to_string());
+        // monkey 0 is done, monkey 1 has thrown one of its items
+        for _ in 0..3 {
+            scalar.step();
+        }
+        let mut batched = scalar.clone();
+        scalar.round();
+        batched.round_batched();
+        assert_same_monkeys(&scalar.monkeys, &batched.monkeys);
+        assert_eq!((batched.position(), batched.turn), ((1, 0), 0));
+    }
+
+    #[test]
+    fn test_same_targets_keep_order() {
+        let monkey = || Monkey {
+            id: 0,
+            items: (1..=19).map(|i| Item { worry_level: i }).collect(),
+            inspections: 0,
+            operation: Operation::parse("new = old * 2".to_string()),
+            test: Test {
+                divisor: 3,
+                true_target: 1,
+                false_target: 1,
+            },
+        };
+        let mut scalar = monkey();
+        let mut batched = monkey();
+        let throws = scalar.inspect_and_throw(&u64::MAX, Relief::None);
+        let batch = batched.inspect_and_throw_batched(&u64::MAX, Relief::None);
+        assert!(batch.false_items.is_empty());
+        assert_eq!(
+            batch.true_items,
+            throws.into_iter().map(|t| t.item).collect::<Vec<_>>()
+        );
+        assert_eq!(batched.inspections, 19);
+    }
+}
@@ -0,0 +1,249 @@
+// Breakpoints for long runs
+//
+// A `Debugger` owns a jungle and steps it one inspection at a time until one of its breakpoints
+// triggers, then hands back what triggered it. The jungle itself can be looked at in between.
+//
+// Items don't have an identity in the jungle, they're just worry levels in a queue. But stepping
+// always moves the first item of a queue to the end of another one, so the debugger numbers the
+// items once (in the order of the input) and moves the numbers along the same way.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{Djungle, Inspection, NumT, Worry};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// An inspection leaves an item with a worry level above this.
+    WorryAbove(NumT),
+    /// A monkey's inspections go above this.
+    InspectionsAbove { monkey: usize, count: NumT },
+    /// A monkey throws away the last item in its queue.
+    QueueEmpty(usize),
+    /// The item with this number is thrown to the monkey.
+    ItemReaches { item: usize, monkey: usize },
+}
+
+// the same syntax `FromStr` takes
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Breakpoint::WorryAbove(level) => write!(f, "worry > {}", level),
+            Breakpoint::InspectionsAbove { monkey, count } => {
+                write!(f, "monkey {} inspections > {}", monkey, count)
+            }
+            Breakpoint::QueueEmpty(monkey) => write!(f, "monkey {} empty", monkey),
+            Breakpoint::ItemReaches { item, monkey } => write!(f, "item {} at {}", item, monkey),
+        }
+    }
+}
+
+impl FromStr for Breakpoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |word: &str| {
+            word.parse::<NumT>()
+                .map_err(|e| format!("{} in breakpoint {}: {}", word, s, e))
+        };
+        let words = s.split_whitespace().collect::<Vec<_>>();
+        match words.as_slice() {
+            ["worry", ">", level] => Ok(Breakpoint::WorryAbove(number(level)?)),
+            ["monkey", monkey, "inspections", ">", count] => Ok(Breakpoint::InspectionsAbove {
+                monkey: number(monkey)? as usize,
+                count: number(count)?,
+            }),
+            ["monkey", monkey, "empty"] => Ok(Breakpoint::QueueEmpty(number(monkey)? as usize)),
+            ["item", item, "at", monkey] => Ok(Breakpoint::ItemReaches {
+                item: number(item)? as usize,
+                monkey: number(monkey)? as usize,
+            }),
+            _ => Err(format!("unknown breakpoint: {}", s)),
+        }
+    }
+}
+
+/// A breakpoint that triggered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hit<W: Worry = NumT> {
+    /// Which of the debugger's breakpoints it was.
+    pub breakpoint: usize,
+    /// The number of the item that was inspected.
+    pub item: usize,
+    pub inspection: Inspection<W>,
+}
+
+pub struct Debugger<W: Worry = NumT> {
+    djungle: Djungle<W>,
+    pub breakpoints: Vec<Breakpoint>,
+    // the numbers of the items every monkey holds, in the order of its queue
+    items: Vec<VecDeque<usize>>,
+}
+
+impl<W: Worry + Into<NumT>> Debugger<W> {
+    pub fn new(djungle: Djungle<W>) -> Self {
+        let mut next = 0;
+        let items = djungle
+            .monkeys
+            .iter()
+            .map(|m| {
+                next += m.items.len();
+                (next - m.items.len()..next).collect()
+            })
+            .collect();
+        Debugger {
+            djungle,
+            breakpoints: Vec::new(),
+            items,
+        }
+    }
+
+    pub fn djungle(&self) -> &Djungle<W> {
+        &self.djungle
+    }
+
+    pub fn into_inner(self) -> Djungle<W> {
+        self.djungle
+    }
+
+    /// The numbers of the items a monkey holds, in the order of its queue.
+    pub fn items(&self, monkey: usize) -> &VecDeque<usize> {
+        &self.items[monkey]
+    }
+
+    /// One inspection, with the number of the item that was inspected.
+    pub fn step(&mut self) -> Option<(usize, Inspection<W>)> {
+        let inspection = self.djungle.step()?;
+        let item = self.items[inspection.monkey].pop_front().unwrap();
+        self.items[inspection.target].push_back(item);
+        Some((item, inspection))
+    }
+
+    /// Steps until a breakpoint triggers, or until the given number of rounds has been played.
+    pub fn run(&mut self, rounds: usize) -> Option<Hit<W>> {
+        while self.djungle.played < rounds {
+            let Some((item, inspection)) = self.step() else {
+                continue;
+            };
+            if let Some(breakpoint) = self
+                .breakpoints
+                .iter()
+                .position(|b| self.triggers(b, item, &inspection))
+            {
+                return Some(Hit {
+                    breakpoint,
+                    item,
+                    inspection,
+                });
+            }
+        }
+        None
+    }
+
+    fn triggers(&self, breakpoint: &Breakpoint, item: usize, inspection: &Inspection<W>) -> bool {
+        let monkey = &self.djungle.monkeys[inspection.monkey];
+        match *breakpoint {
+            Breakpoint::WorryAbove(level) => inspection.after.clone().into() > level,
+            // only the monkey that just inspected can have gone above it
+            Breakpoint::InspectionsAbove { monkey: m, count } => {
+                m == inspection.monkey && monkey.inspections == count + 1
+            }
+            Breakpoint::QueueEmpty(m) => m == inspection.monkey && monkey.items.is_empty(),
+            Breakpoint::ItemReaches { item: i, monkey: m } => i == item && m == inspection.target,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parsable;
+
+    fn test_debugger(breakpoints: &[&str]) -> Debugger<u64> {
+        let mut debugger = Debugger::new(Djungle::parse(include_str!("../test.txt").to_string()));
+        for breakpoint in breakpoints {
+            debugger.breakpoints.push(breakpoint.parse().unwrap());
+        }
+        debugger
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        for breakpoint in [
+            "worry > 1000",
+            "monkey 3 inspections > 100",
+            "monkey 0 empty",
+            "item 4 at 2",
+        ] {
+            let parsed = breakpoint.parse::<Breakpoint>().unwrap();
+            assert_eq!(parsed.to_string(), breakpoint);
+        }
+        assert!("monkey 3 bored".parse::<Breakpoint>().is_err());
+        assert!("worry > lots".parse::<Breakpoint>().is_err());
+    }
+
+    #[test]
+    fn test_queue_empty() {
+        let mut debugger = test_debugger(&["monkey 0 empty"]);
+        // monkey 0 starts with two items
+        let hit = debugger.run(1).unwrap();
+        assert_eq!(hit.item, 1);
+        assert_eq!(debugger.djungle().position(), (0, 2));
+        // next round it has more to throw
+        let hit = debugger.run(2).unwrap();
+        assert_eq!(hit.inspection.round, 2);
+    }
+
+    #[test]
+    fn test_inspections_above() {
+        let mut debugger = test_debugger(&["monkey 3 inspections > 100"]);
+        let hit = debugger.run(10000).unwrap();
+        assert_eq!(hit.inspection.monkey, 3);
+        assert_eq!(debugger.djungle().monkeys[3].inspections, 101);
+        // it only triggers when going above, not afterwards
+        assert_eq!(debugger.run(100), None);
+    }
+
+    #[test]
+    fn test_worry_above() {
+        let mut debugger = test_debugger(&["worry > 9000000"]);
+        let hit = debugger.run(10000).unwrap();
+        assert!(hit.inspection.after > 9000000);
+        // nothing that high before, the same jungle stepped without breakpoints says so
+        let mut fresh = test_debugger(&[]);
+        let mut levels = vec![];
+        while fresh.djungle().position() < debugger.djungle().position() {
+            if let Some((_, inspection)) = fresh.step() {
+                levels.push(inspection.after);
+            }
+        }
+        assert_eq!(levels.pop(), Some(hit.inspection.after));
+        assert!(levels.iter().all(|level| *level <= 9000000));
+    }
+
+    #[test]
+    fn test_items_are_followed() {
+        // item 0 starts at monkey 0 with worry level 79 and goes to monkey 3 first
+        let mut debugger = test_debugger(&["item 0 at 3", "item 0 at 1"]);
+        let hit = debugger.run(1).unwrap();
+        assert_eq!((hit.breakpoint, hit.item), (0, 0));
+        assert_eq!(hit.inspection.before, 79);
+        assert_eq!(debugger.items(3).back(), Some(&0));
+        let hit = debugger.run(10000).unwrap();
+        assert_eq!(hit.item, 0);
+        assert_eq!(hit.inspection.target, 1);
+        // every item is somewhere, exactly once
+        let mut all = (0..4)
+            .flat_map(|m| debugger.items(m).iter().copied())
+            .collect::<Vec<_>>();
+        all.sort();
+        assert_eq!(all, (0..10).collect::<Vec<_>>());
+        for monkey in 0..4 {
+            assert_eq!(
+                debugger.items(monkey).len(),
+                debugger.djungle().monkeys[monkey].items.len()
+            );
+        }
+    }
+}
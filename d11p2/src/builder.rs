@@ -0,0 +1,252 @@
+// Putting a jungle together in code
+//
+//     let djungle: Djungle = Djungle::builder()
+//         .monkey(|m| m.items([79, 98]).mul(19).divisible_by(23).throw(2, 3))
+//         .monkey(|m| m.items([54, 65, 75, 74]).add(6).divisible_by(19).throw(2, 0))
+//         .build()?;
+//
+// Monkeys get their ids in the order they're added. Whatever can be checked right away is
+// (a missing operation, a divisor of 0, an operation set twice), throw targets are checked in
+// `build`, when all monkeys are there, the same way the parser checks them. The first problem is
+// the one `build` reports, worded like the parser's errors. A jungle the worry type can't hold is
+// an error as well.
+
+use std::marker::PhantomData;
+
+use crate::monkey::check_targets;
+use crate::{
+    Djungle, Item, Monkey, NumT, Operation, OperationParameter, OperationType, Test, Worry,
+};
+
+pub struct DjungleBuilder<W: Worry = NumT> {
+    monkeys: Vec<Monkey>,
+    error: Option<String>,
+    worry: PhantomData<W>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MonkeyBuilder {
+    items: Vec<NumT>,
+    operation: Option<Operation>,
+    divisor: Option<NumT>,
+    targets: Option<(usize, usize)>,
+    error: Option<String>,
+}
+
+impl<W: Worry> Djungle<W> {
+    pub fn builder() -> DjungleBuilder<W> {
+        DjungleBuilder {
+            monkeys: Vec::new(),
+            error: None,
+            worry: PhantomData,
+        }
+    }
+}
+
+impl<W: Worry> DjungleBuilder<W> {
+    /// Adds the next monkey.
+    pub fn monkey(mut self, build: impl FnOnce(MonkeyBuilder) -> MonkeyBuilder) -> Self {
+        let id = self.monkeys.len();
+        match build(MonkeyBuilder::default()).finish(id) {
+            Ok(monkey) => self.monkeys.push(monkey),
+            Err(e) => {
+                self.error.get_or_insert(e);
+            }
+        }
+        self
+    }
+
+    /// The monkeys the way `Vec::<Monkey>::parse` has them.
+    pub fn build_monkeys(self) -> Result<Vec<Monkey>, String> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        check_targets(&self.monkeys)?;
+        Ok(self.monkeys)
+    }
+
+    pub fn build(self) -> Result<Djungle<W>, String> {
+        Djungle::try_lift(self.build_monkeys()?)
+    }
+}
+
+// named after what the monkey does to the worry level, they're not meant to be `+` and `*`
+#[allow(clippy::should_implement_trait)]
+impl MonkeyBuilder {
+    pub fn items(mut self, items: impl IntoIterator<Item = NumT>) -> Self {
+        self.items.extend(items);
+        self
+    }
+
+    /// new = old + c
+    pub fn add(self, c: NumT) -> Self {
+        self.operation(OperationType::Add, OperationParameter::Constant(c))
+    }
+
+    /// new = old * c
+    pub fn mul(self, c: NumT) -> Self {
+        self.operation(OperationType::Multiply, OperationParameter::Constant(c))
+    }
+
+    /// new = old + old
+    pub fn double(self) -> Self {
+        self.operation(OperationType::Add, OperationParameter::Old)
+    }
+
+    /// new = old * old
+    pub fn square(self) -> Self {
+        self.operation(OperationType::Multiply, OperationParameter::Old)
+    }
+
+    pub fn divisible_by(mut self, divisor: NumT) -> Self {
+        if divisor == 0 {
+            self.fail("is divisible by 0".to_string());
+        } else if self.divisor.replace(divisor).is_some() {
+            self.fail("has two tests".to_string());
+        }
+        self
+    }
+
+    /// Where the item goes if the test is true, and where if it isn't.
+    pub fn throw(mut self, true_target: usize, false_target: usize) -> Self {
+        if self.targets.replace((true_target, false_target)).is_some() {
+            self.fail("throws twice".to_string());
+        }
+        self
+    }
+
+    fn operation(mut self, operation: OperationType, right: OperationParameter) -> Self {
+        if self
+            .operation
+            .replace(Operation { operation, right })
+            .is_some()
+        {
+            self.fail("has two operations".to_string());
+        }
+        self
+    }
+
+    // only the first problem counts, later ones might just follow from it
+    fn fail(&mut self, problem: String) {
+        self.error.get_or_insert(problem);
+    }
+
+    fn finish(self, id: usize) -> Result<Monkey, String> {
+        let missing = if self.operation.is_none() {
+            Some("has no operation")
+        } else if self.divisor.is_none() {
+            Some("has no test")
+        } else if self.targets.is_none() {
+            Some("doesn't throw anywhere")
+        } else {
+            None
+        };
+        if let Some(problem) = self.error.as_deref().or(missing) {
+            return Err(format!("Monkey {} {}", id, problem));
+        }
+        let (true_target, false_target) = self.targets.unwrap();
+        Ok(Monkey {
+            id,
+            items: self
+                .items
+                .into_iter()
+                .map(|worry_level| Item { worry_level })
+                .collect(),
+            inspections: 0,
+            operation: self.operation.unwrap(),
+            test: Test {
+                divisor: self.divisor.unwrap(),
+                true_target,
+                false_target,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{to_input, Parsable};
+
+    fn test_builder<W: Worry>() -> DjungleBuilder<W> {
+        Djungle::builder()
+            .monkey(|m| m.items([79, 98]).mul(19).divisible_by(23).throw(2, 3))
+            .monkey(|m| {
+                m.items([54, 65, 75, 74])
+                    .add(6)
+                    .divisible_by(19)
+                    .throw(2, 0)
+            })
+            .monkey(|m| m.items([79, 60, 97]).square().divisible_by(13).throw(1, 3))
+            .monkey(|m| m.items([74]).add(3).divisible_by(17).throw(0, 1))
+    }
+
+    #[test]
+    fn test_same_as_parsed() {
+        let built = test_builder::<NumT>().build_monkeys().unwrap();
+        let parsed = Vec::<Monkey>::parse(include_str!("../test.txt").to_string());
+        assert_eq!(to_input(&built), to_input(&parsed));
+    }
+
+    #[test]
+    fn test_build_and_play() {
+        let mut djungle: Djungle<u64> = test_builder().build().unwrap();
+        for _ in 0..10000 {
+            djungle.round();
+        }
+        assert_eq!(djungle.monkey_business(), 2713310158);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let error = |builder: DjungleBuilder| builder.build_monkeys().unwrap_err();
+        assert_eq!(
+            error(test_builder().monkey(|m| m.items([1]).divisible_by(2).throw(0, 1))),
+            "Monkey 4 has no operation"
+        );
+        assert_eq!(
+            error(test_builder().monkey(|m| m.double().divisible_by(0).throw(0, 1))),
+            "Monkey 4 is divisible by 0"
+        );
+        assert_eq!(
+            error(test_builder().monkey(|m| m.add(1).mul(2).divisible_by(2).throw(0, 1))),
+            "Monkey 4 has two operations"
+        );
+        assert_eq!(
+            error(test_builder().monkey(|m| m.square().divisible_by(2))),
+            "Monkey 4 doesn't throw anywhere"
+        );
+        assert_eq!(
+            error(test_builder().monkey(|m| m.square().divisible_by(2).throw(0, 5))),
+            "Monkey 4 throws to monkey 5, there are only 5"
+        );
+        // the first problem is the one reported
+        assert_eq!(
+            error(
+                Djungle::builder()
+                    .monkey(|m| m.square().throw(1, 1))
+                    .monkey(|m| m.add(1))
+            ),
+            "Monkey 0 has no test"
+        );
+    }
+
+    #[test]
+    fn test_too_big_for_worry_type() {
+        // a mod factor of 2^80
+        fn builder<W: Worry>() -> DjungleBuilder<W> {
+            Djungle::builder()
+                .monkey(|m| m.items([1]).add(1).divisible_by(1 << 40).throw(1, 1))
+                .monkey(|m| m.items([1]).add(1).divisible_by(1 << 40).throw(0, 0))
+        }
+        assert_eq!(
+            builder::<u64>().build().unwrap_err(),
+            "mod factor does not fit into a u64"
+        );
+        assert!(builder::<u128>().build().is_ok());
+        let built = Djungle::<u32>::builder()
+            .monkey(|m| m.items([1 << 40]).add(1).divisible_by(2).throw(0, 0))
+            .build();
+        assert!(built.is_err());
+    }
+}
@@ -0,0 +1,197 @@
+// Compiled monkeys
+//
+// `Operation::evaluate` matches on the operation type and the parameter for every single item, and
+// `Test::throw` looks up the targets every time. None of that changes after parsing, so we can
+// resolve it once per monkey into closures that only do the arithmetic.
+
+use crate::{
+    Djungle, Item, NumT, Operation, OperationParameter, OperationType, Relief, Test, Worry,
+};
+
+pub type CompiledOperation<W> = Box<dyn Fn(&W, &<W as Worry>::Modulus) -> W>;
+pub type CompiledTest<W> = Box<dyn Fn(&W, &<W as Worry>::Modulus) -> usize>;
+
+impl Operation {
+    pub fn compile<W: Worry + 'static>(&self) -> CompiledOperation<W> {
+        match (&self.operation, &self.right) {
+            (OperationType::Add, OperationParameter::Constant(c)) => {
+                let c = *c;
+                Box::new(move |old, m| old.add_constant(c, m))
+            }
+            (OperationType::Add, OperationParameter::Old) => Box::new(|old, m| old.add(old, m)),
+            (OperationType::Multiply, OperationParameter::Constant(c)) => {
+                let c = *c;
+                Box::new(move |old, m| old.mul_constant(c, m))
+            }
+            (OperationType::Multiply, OperationParameter::Old) => {
+                Box::new(|old, m| old.mul(old, m))
+            }
+        }
+    }
+}
+
+impl Test {
+    pub fn compile<W: Worry + 'static>(&self) -> CompiledTest<W> {
+        let (divisor, true_target, false_target) =
+            (self.divisor, self.true_target, self.false_target);
+        Box::new(move |worry, m| {
+            if worry.is_divisible_by(divisor, m) {
+                true_target
+            } else {
+                false_target
+            }
+        })
+    }
+}
+
+pub struct CompiledMonkey<W: Worry> {
+    pub operation: CompiledOperation<W>,
+    pub test: CompiledTest<W>,
+}
+
+/// A jungle that plays its rounds with compiled monkeys instead of interpreting them.
+pub struct CompiledDjungle<W: Worry> {
+    pub djungle: Djungle<W>,
+    compiled: Vec<CompiledMonkey<W>>,
+    // the items of the monkey whose turn it is, swapped in so we don't allocate every turn
+    turn: Vec<Item<W>>,
+}
+
+impl<W: Worry + 'static> CompiledDjungle<W> {
+    pub fn new(djungle: Djungle<W>) -> Self {
+        let compiled = djungle
+            .monkeys
+            .iter()
+            .map(|m| CompiledMonkey {
+                operation: m.operation.compile(),
+                test: m.test.compile(),
+            })
+            .collect();
+        CompiledDjungle {
+            djungle,
+            compiled,
+            turn: Vec::new(),
+        }
+    }
+
+    pub fn round(&mut self) {
+        let djungle = &mut self.djungle;
+        // after stepping, the round is finished from wherever we are
+        for (monkey_id, compiled) in self.compiled.iter().enumerate().skip(djungle.turn) {
+            std::mem::swap(&mut self.turn, &mut djungle.monkeys[monkey_id].items);
+            djungle.monkeys[monkey_id].inspections += self.turn.len() as NumT;
+            for mut item in self.turn.drain(..) {
+                match djungle.relief() {
+                    Relief::DivideByThree => {
+                        item.worry_level =
+                            (compiled.operation)(&item.worry_level, &djungle.mod_factor);
+                        item.worry_level.divide_by_three();
+                    }
+                    Relief::None => {
+                        item.worry_level.reduce(&djungle.mod_factor);
+                        item.worry_level =
+                            (compiled.operation)(&item.worry_level, &djungle.mod_factor);
+                    }
+                }
+                let target = (compiled.test)(&item.worry_level, &djungle.mod_factor);
+                djungle.monkeys[target].catch(item);
+            }
+        }
+        djungle.turn = 0;
+        djungle.steps = 0;
+        djungle.played += 1;
+    }
+
+    pub fn monkey_business(&self) -> NumT {
+        self.djungle.monkey_business()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{assert_same_as_plain, assert_same_monkeys};
+    use crate::{Barrett, Parsable, Residues};
+
+    fn operations() -> Vec<Operation> {
+        [
+            "new = old + 7",
+            "new = old + old",
+            "new = old * 19",
+            "new = old * old",
+        ]
+        .iter()
+        .map(|o| Operation::parse(o.to_string()))
+        .collect()
+    }
+
+    fn assert_same_operations<W: Worry + 'static>(mod_factor: &W::Modulus, levels: &[NumT]) {
+        for operation in operations() {
+            let compiled = operation.compile::<W>();
+            for level in levels {
+                let old = W::lift(*level, mod_factor);
+                assert_eq!(
+                    compiled(&old, mod_factor),
+                    operation.evaluate(&old, mod_factor),
+                    "{:?} on {}",
+                    operation,
+                    level
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_operations_match_evaluate() {
+        let divisors = [23, 19, 13, 17];
+        let levels = [0, 1, 2, 79, 96576, 123456];
+        assert_same_operations::<u64>(&u64::modulus(&divisors).unwrap(), &levels);
+        assert_same_operations::<u128>(&u128::modulus(&divisors).unwrap(), &levels);
+        assert_same_operations::<Barrett>(&Barrett::modulus(&divisors).unwrap(), &levels);
+        assert_same_operations::<Residues>(&Residues::modulus(&divisors).unwrap(), &levels);
+    }
+
+    #[test]
+    fn test_tests_match_throw() {
+        let test = Test::parse(
+            "Test: divisible by 13\n  If true: throw to monkey 1\n  If false: throw to monkey 3"
+                .to_string(),
+        );
+        let compiled = test.compile::<u64>();
+        for level in 0..100 {
+            let item = Item { worry_level: level };
+            assert_eq!(compiled(&level, &13), test.throw(item, &13).target);
+        }
+    }
+
+    #[test]
+    fn test_rounds_match_interpreted() {
+        for relief in [Relief::DivideByThree, Relief::None] {
+            assert_same_as_plain::<u64, _>(
+                relief,
+                20,
+                CompiledDjungle::new,
+                CompiledDjungle::round,
+                |interpreted, compiled| {
+                    assert_same_monkeys(&interpreted.monkeys, &compiled.djungle.monkeys);
+                    assert_eq!(interpreted.played, compiled.djungle.played);
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn test_round_after_stepping() {
+        let mut interpreted: Djungle<u64> = Djungle::parse(include_str!("../test.txt").to_string());
+        // monkey 0 is done, monkey 1 has thrown one of its items
+        for _ in 0..3 {
+            interpreted.step();
+        }
+        let mut compiled = CompiledDjungle::new(interpreted.clone());
+        interpreted.round();
+        compiled.round();
+        assert_same_monkeys(&interpreted.monkeys, &compiled.djungle.monkeys);
+        assert_eq!(compiled.djungle.position(), (1, 0));
+        assert_eq!(compiled.djungle.turn, 0);
+    }
+}
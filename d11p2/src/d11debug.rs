@@ -0,0 +1,69 @@
+// Run until a breakpoint triggers, show what happened, continue on enter
+//
+// cargo run --bin d11debug -- input.txt 10000 "worry > 1000000" "monkey 3 empty" "item 4 at 2"
+// Other breakpoints: "monkey <n> inspections > <count>"
+
+use std::io::{self, BufRead};
+
+use djungle::{Breakpoint, Debugger, Djungle, Parsable};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let path = args.next().unwrap_or_else(|| "input.txt".to_string());
+    let rounds = match args.next() {
+        Some(rounds) => rounds.parse::<usize>().unwrap(),
+        None => 10000,
+    };
+    let input = std::fs::read_to_string(&path).unwrap();
+    let djungle: Djungle = Djungle::parse(input);
+
+    let mut debugger = Debugger::new(djungle);
+    for breakpoint in args {
+        match breakpoint.parse::<Breakpoint>() {
+            Ok(breakpoint) => debugger.breakpoints.push(breakpoint),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let stdin = io::stdin();
+    while let Some(hit) = debugger.run(rounds) {
+        let inspection = &hit.inspection;
+        println!(
+            "Round {}: {} (monkey {} inspected item {}: {} -> {}, threw it to monkey {})",
+            inspection.round,
+            debugger.breakpoints[hit.breakpoint],
+            inspection.monkey,
+            hit.item,
+            inspection.before,
+            inspection.after,
+            inspection.target
+        );
+        for (id, monkey) in debugger.djungle().monkeys.iter().enumerate() {
+            let items = debugger
+                .items(id)
+                .iter()
+                .zip(monkey.items.iter())
+                .map(|(item, level)| format!("#{}={}", item, level.worry_level))
+                .collect::<Vec<_>>();
+            println!(
+                "  Monkey {} ({} inspections): {}",
+                id,
+                monkey.inspections,
+                items.join(", ")
+            );
+        }
+        println!("enter to continue, q to quit");
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 || line.trim() == "q" {
+            return;
+        }
+    }
+    println!(
+        "Played {} rounds, monkey business: {}",
+        debugger.djungle().played,
+        debugger.djungle().monkey_business()
+    );
+}
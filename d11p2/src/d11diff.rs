@@ -0,0 +1,107 @@
+// Find the first throw where two runs disagree
+//
+// cargo run --bin d11diff -- logs left.log right.log [no-worry]
+// cargo run --bin d11diff -- run input.txt 10000 u32 u64 [part1]
+//
+// Runs wrap around instead of panicking when a worry level doesn't fit, so a backend that's too
+// narrow shows up as the first throw where its worry level wrapped around.
+
+use djungle::{
+    first_divergence, Backend, Djungle, Monkey, NumT, Overflowing, Parsable, Relief, ThrowLog,
+    Worry,
+};
+
+const CONTEXT: usize = 5;
+
+fn usage() -> ! {
+    eprintln!("usage: d11diff logs <left log> <right log> [no-worry]");
+    eprintln!("       d11diff run <input> <rounds> <left backend> <right backend> [part1]");
+    std::process::exit(2);
+}
+
+// also says in which round a worry level first overflowed, if one did
+fn record_wrapping<T>(
+    monkeys: Vec<Monkey>,
+    relief: Relief,
+    rounds: usize,
+) -> (ThrowLog, Option<usize>)
+where
+    Overflowing<T>: Worry + Into<NumT>,
+{
+    let mut djungle = Djungle::<Overflowing<T>>::lift(monkeys).with_relief(relief);
+    let mut log = ThrowLog::new(relief);
+    let mut overflowed = None;
+    for round in 1..=rounds {
+        djungle.round_logged(&mut log);
+        if overflowed.is_none() && djungle.overflowed() {
+            overflowed = Some(round);
+        }
+    }
+    (log, overflowed)
+}
+
+fn record(backend: Backend, monkeys: Vec<Monkey>, relief: Relief, rounds: usize) -> ThrowLog {
+    let (log, overflowed) = match backend {
+        Backend::U32 => record_wrapping::<u32>(monkeys, relief, rounds),
+        Backend::U64 => record_wrapping::<u64>(monkeys, relief, rounds),
+        Backend::U128 => record_wrapping::<u128>(monkeys, relief, rounds),
+        Backend::Residues => {
+            eprintln!("residues don't keep worry levels as numbers, they can't be logged");
+            std::process::exit(2);
+        }
+    };
+    if let Some(round) = overflowed {
+        println!(
+            "The {} run overflowed in round {}, its worry levels wrap around from there",
+            backend, round
+        );
+    }
+    log
+}
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(|a| a.as_str()).collect::<Vec<_>>();
+    let (left, right, compare_worry) = match args.as_slice() {
+        ["logs", left, right, rest @ ..] => {
+            let compare_worry = match rest {
+                [] => true,
+                ["no-worry"] => false,
+                _ => usage(),
+            };
+            let read = |path: &str| ThrowLog::from_bytes(&std::fs::read(path).unwrap()).unwrap();
+            let (left, right) = (read(left), read(right));
+            if left.relief != right.relief {
+                println!(
+                    "The runs used different relief: {:?} and {:?}",
+                    left.relief, right.relief
+                );
+            }
+            (left, right, compare_worry)
+        }
+        ["run", input, rounds, left, right, rest @ ..] => {
+            let relief = match rest {
+                [] => Relief::None,
+                ["part1"] => Relief::DivideByThree,
+                _ => usage(),
+            };
+            let monkeys = Vec::<Monkey>::parse(std::fs::read_to_string(input).unwrap());
+            let rounds = rounds.parse::<usize>().unwrap();
+            let backend = |name: &str| name.parse::<Backend>().unwrap_or_else(|_| usage());
+            (
+                record(backend(left), monkeys.clone(), relief, rounds),
+                record(backend(right), monkeys, relief, rounds),
+                true,
+            )
+        }
+        _ => usage(),
+    };
+
+    match first_divergence(&left.throws, &right.throws, compare_worry, CONTEXT) {
+        Some(divergence) => {
+            print!("{}", divergence);
+            std::process::exit(1);
+        }
+        None => println!("Both runs made the same {} throws", left.throws.len()),
+    }
+}
@@ -0,0 +1,30 @@
+// Write down what the code does with an input now, for a fixture directory (see regression.rs)
+//
+// cargo run --bin d11expect -- input.txt part2 10000 1 20 1000 > fixtures/input/part2.txt
+
+use djungle::{Expected, Monkey, Parsable, Relief};
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let [input, part, rounds, after @ ..] = args.as_slice() else {
+        eprintln!("usage: d11expect <input> <part1|part2> <rounds> [after round]...");
+        std::process::exit(2);
+    };
+    let relief = match part.as_str() {
+        "part1" => Relief::DivideByThree,
+        "part2" => Relief::None,
+        _ => {
+            eprintln!("unknown part {}, part1 or part2", part);
+            std::process::exit(2);
+        }
+    };
+    let monkeys = Vec::<Monkey>::parse(std::fs::read_to_string(input).unwrap());
+    let rounds = rounds.parse::<usize>().unwrap();
+    let after = after
+        .iter()
+        .map(|r| r.parse::<usize>().unwrap())
+        .collect::<Vec<_>>();
+
+    println!("# {} {}, written by d11expect", input, part);
+    print!("{}", Expected::record(&monkeys, relief, rounds, &after));
+}
@@ -0,0 +1,19 @@
+// Print a monkey file as Rust code that builds the same jungle, for regression tests
+//
+// cargo run --bin d11fixture -- input.txt literals input_fixture >> src/some_tests.rs
+
+use djungle::{fixture_code, FixtureStyle, Monkey, Parsable};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let path = args.next().unwrap_or_else(|| "input.txt".to_string());
+    let style = args
+        .next()
+        .map(|s| s.parse::<FixtureStyle>().unwrap())
+        .unwrap_or_default();
+    let name = args.next().unwrap_or_else(|| "djungle_fixture".to_string());
+
+    let monkeys = Vec::<Monkey>::parse(std::fs::read_to_string(&path).unwrap());
+    println!("// {} as {}, generated by d11fixture", path, style);
+    print!("{}", fixture_code(&monkeys, &name, style));
+}
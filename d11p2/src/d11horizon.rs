@@ -0,0 +1,22 @@
+use djungle::{overflow_horizon, Horizon, Monkey, Parsable, Relief};
+
+fn main() {
+    // how long each worry type is safe, for both parts
+    let input = std::fs::read_to_string("input.txt").unwrap();
+    let monkeys = Vec::<Monkey>::parse(input);
+    let rounds = match std::env::args().nth(1) {
+        Some(rounds) => rounds.parse::<usize>().unwrap(),
+        None => 20,
+    };
+
+    for relief in [Relief::DivideByThree, Relief::None] {
+        for bits in [32, 64, 128] {
+            let verdict = match overflow_horizon(&monkeys, relief, bits, rounds) {
+                Horizon::Overflow(round) => format!("might overflow in round {}", round),
+                Horizon::Never => "never overflows".to_string(),
+                Horizon::Beyond(rounds) => format!("safe for {} rounds", rounds),
+            };
+            println!("{:?}, u{}: {}", relief, bits, verdict);
+        }
+    }
+}
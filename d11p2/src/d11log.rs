@@ -0,0 +1,53 @@
+// Record the throws of a run, or check that the current code still throws the same way
+//
+// cargo run --bin d11log -- record input.txt 10000 throws.log [part1]
+// cargo run --bin d11log -- verify input.txt throws.log
+
+use djungle::{Djungle, Parsable, Relief, ThrowLog};
+
+fn usage() -> ! {
+    eprintln!("usage: d11log record <input> <rounds> <log> [part1]");
+    eprintln!("       d11log verify <input> <log>");
+    std::process::exit(2);
+}
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(|a| a.as_str()).collect::<Vec<_>>();
+    match args.as_slice() {
+        ["record", input, rounds, log, rest @ ..] => {
+            let relief = match rest {
+                [] => Relief::None,
+                ["part1"] => Relief::DivideByThree,
+                _ => usage(),
+            };
+            let input = std::fs::read_to_string(input).unwrap();
+            let mut djungle: Djungle = Djungle::parse(input).with_relief(relief);
+            let throws = ThrowLog::record(&mut djungle, rounds.parse().unwrap());
+            std::fs::write(log, throws.to_bytes()).unwrap();
+            println!(
+                "Recorded {} throws in {} rounds, monkey business: {}",
+                throws.throws.len(),
+                throws.rounds,
+                djungle.monkey_business()
+            );
+        }
+        ["verify", input, log] => {
+            let input = std::fs::read_to_string(input).unwrap();
+            let djungle: Djungle = Djungle::parse(input);
+            let throws = ThrowLog::from_bytes(&std::fs::read(log).unwrap()).unwrap();
+            match throws.replay(djungle) {
+                Ok(()) => println!(
+                    "All {} throws of {} rounds reproduced",
+                    throws.throws.len(),
+                    throws.rounds
+                ),
+                Err(mismatch) => {
+                    println!("{}", mismatch);
+                    std::process::exit(1);
+                }
+            }
+        }
+        _ => usage(),
+    }
+}
@@ -0,0 +1,46 @@
+// Shrink an input for as long as a command still fails on it
+//
+// The command runs once per candidate, with {input} replaced by the path of a file holding the
+// candidate and {rounds} by the number of rounds. Anything but a successful exit counts as still
+// failing, so a command that panics or reports a difference both work:
+//
+// cargo run --release --bin d11minimise -- input.txt 10000 target/release/d11diff run {input} {rounds} u32 u64
+
+use std::process::{Command, Stdio};
+
+use djungle::{minimise, to_input, Monkey, Parsable};
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let [input, rounds, program, args @ ..] = args.as_slice() else {
+        eprintln!("usage: d11minimise <input> <rounds> <command> [args]...");
+        eprintln!("       {{input}} and {{rounds}} in the arguments are replaced for every try");
+        std::process::exit(2);
+    };
+    let monkeys = Vec::<Monkey>::parse(std::fs::read_to_string(input).unwrap());
+    let rounds = rounds.parse::<usize>().unwrap();
+    let candidate = std::env::temp_dir().join(format!("d11minimise-{}.txt", std::process::id()));
+
+    let fails = |monkeys: &[Monkey], rounds: usize| {
+        std::fs::write(&candidate, to_input(monkeys)).unwrap();
+        let args = args.iter().map(|a| {
+            a.replace("{input}", &candidate.to_string_lossy())
+                .replace("{rounds}", &rounds.to_string())
+        });
+        let status = Command::new(program)
+            .args(args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .unwrap();
+        !status.success()
+    };
+    let reproducer = minimise(monkeys, rounds, fails);
+    std::fs::remove_file(&candidate).unwrap();
+
+    eprintln!(
+        "Still fails after {} rounds, tried {} inputs:",
+        reproducer.rounds, reproducer.tries
+    );
+    print!("{}", reproducer.input());
+}
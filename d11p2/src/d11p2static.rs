@@ -0,0 +1,51 @@
+// The jungle from input.txt, compiled into the binary by build.rs. Nothing is parsed at runtime.
+
+include!(concat!(env!("OUT_DIR"), "/jungle.rs"));
+
+// each part has its own worry type, and its own `starting_items` and `round` for it
+fn monkey_business<W>(
+    rounds: usize,
+    mut items: [Vec<W>; MONKEYS],
+    round: fn(&mut [Vec<W>; MONKEYS], &mut [u64; MONKEYS]),
+) -> u64 {
+    let mut inspections = [0; MONKEYS];
+    for _ in 0..rounds {
+        round(&mut items, &mut inspections);
+    }
+    inspections.sort_unstable();
+    inspections.iter().rev().take(2).product()
+}
+
+fn part1() -> u64 {
+    monkey_business(part1::ROUNDS, part1::starting_items(), part1::round)
+}
+
+fn part2(rounds: usize) -> u64 {
+    monkey_business(rounds, part2::starting_items(), part2::round)
+}
+
+fn main() {
+    println!("Part 1: {}", part1());
+    println!("Part 2: {}", part2(10000));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use djungle::{Djungle, Parsable, Relief};
+
+    fn expected(rounds: usize, relief: Relief) -> u64 {
+        let input = std::fs::read_to_string(env!("DJUNGLE_SOURCE")).unwrap();
+        let mut dj: Djungle = Djungle::parse(input).with_relief(relief);
+        for _ in 0..rounds {
+            dj.round();
+        }
+        dj.monkey_business() as u64
+    }
+
+    #[test]
+    fn test_same_monkey_business_as_engine() {
+        assert_eq!(part1(), expected(part1::ROUNDS, Relief::DivideByThree));
+        assert_eq!(part2(10000), expected(10000, Relief::None));
+    }
+}
@@ -0,0 +1,35 @@
+use djungle::{Monkey, Parsable, StateSpace};
+
+fn main() {
+    // follow every item through its states instead of playing the rounds
+    let input = std::fs::read_to_string("input.txt").unwrap();
+    let monkeys = Vec::<Monkey>::parse(input);
+    let rounds = match std::env::args().nth(1) {
+        Some(rounds) => rounds.parse::<usize>().unwrap(),
+        None => 10000,
+    };
+
+    let Some(space) = StateSpace::explore(&monkeys) else {
+        println!("Worry levels don't fit into a u128, nothing to explore");
+        return;
+    };
+    for (item, orbit) in space.orbits.iter().enumerate() {
+        let (monkey, worry) = orbit.states[0];
+        println!(
+            "Item {} (monkey {}, worry {}): {} states, transient {}, cycle {}",
+            item,
+            monkey,
+            worry,
+            orbit.states.len(),
+            orbit.transient,
+            orbit.cycle
+        );
+    }
+    println!("{} different states overall", space.size());
+    println!(
+        "Inspections after {} rounds: {:?}",
+        rounds,
+        space.inspections_after(rounds)
+    );
+    println!("Monkey business: {}", space.monkey_business_after(rounds));
+}
@@ -0,0 +1,179 @@
+// Step through the monkey business in the terminal
+//
+// Shows every monkey side by side (queue, operation, test, inspections) and reads one command per
+// line from stdin, so it works in any terminal without extra crates. Type `h` for the commands.
+//
+// Toggling relief can leave worry levels that grow without bound, so the jungle is played with
+// levels that notice when they overflow a u128. A command that overflows is undone, going forward
+// stops at the last round that doesn't.
+
+use std::io::{self, BufRead, Write};
+
+use djungle::{Djungle, History, Inspection, Overflowing, Parsable, Relief};
+
+const COLUMN: usize = 22;
+const MAX_ITEMS: usize = 12;
+
+const HELP: &str = "\
+  s, <enter>  step one inspection
+  t           finish the current monkey's turn
+  r           play one round
+  g <rounds>  go to where that many rounds have been played (backwards works too, forwards
+              stops before a round that would overflow)
+  b           step back one inspection
+  p           toggle relief (divide by three / none)
+  h           show this help
+  q           quit";
+
+type Level = Overflowing<u128>;
+
+fn render(djungle: &Djungle<Level>, last: &str) -> String {
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    for (id, monkey) in djungle.monkeys.iter().enumerate() {
+        let marker = if id == djungle.turn { ">" } else { " " };
+        let mut column = vec![
+            format!("{}Monkey {}", marker, id),
+            monkey.operation.to_string(),
+            format!("divisible by {}", monkey.test.divisor),
+            format!(
+                "? {} : {}",
+                monkey.test.true_target, monkey.test.false_target
+            ),
+            format!("inspected {}", monkey.inspections),
+            "-".repeat(COLUMN - 2),
+        ];
+        for item in monkey.items.iter().take(MAX_ITEMS) {
+            column.push(item.worry_level.to_string());
+        }
+        if monkey.items.len() > MAX_ITEMS {
+            column.push(format!("... {} more", monkey.items.len() - MAX_ITEMS));
+        }
+        rows.push(column);
+    }
+
+    let height = rows.iter().map(|c| c.len()).max().unwrap_or(0);
+    let mut screen = format!(
+        "{} rounds played, {} inspections into the next, relief: {:?}, monkey business: {}\n\n",
+        djungle.played,
+        djungle.steps,
+        djungle.relief(),
+        djungle.monkey_business()
+    );
+    for line in 0..height {
+        for column in rows.iter() {
+            let cell = column.get(line).map(|s| s.as_str()).unwrap_or("");
+            // cut off whatever doesn't fit
+            let cell = cell.chars().take(COLUMN - 1).collect::<String>();
+            screen.push_str(&format!("{:<width$}", cell, width = COLUMN));
+        }
+        screen.push('\n');
+    }
+    screen.push_str(&format!("\n{}\n", last));
+    screen
+}
+
+fn describe(inspection: Option<Inspection<Level>>) -> String {
+    match inspection {
+        Some(i) => format!(
+            "Monkey {} inspected {}, now {}, threw it to monkey {}",
+            i.monkey, i.before, i.after, i.target
+        ),
+        None => "Round over".to_string(),
+    }
+}
+
+// steps until the monkey that inspected first is done with its turn
+fn finish_turn(history: &mut History<Level>) -> String {
+    let Some(first) = history.step() else {
+        return describe(None);
+    };
+    let monkey = first.monkey;
+    let mut count = 1;
+    while history.djungle().turn == monkey && !history.djungle().monkeys[monkey].items.is_empty() {
+        history.step();
+        count += 1;
+    }
+    format!("Monkey {} inspected {} items", monkey, count)
+}
+
+// forwards one round at a time, to stop before the first one that overflows
+fn go_to(history: &mut History<Level>, round: usize) -> String {
+    let mut last = history.djungle().position();
+    if round <= last.0 {
+        return match history.goto_round(round) {
+            true => format!("Went to round {}", round),
+            false => "Can't go there".to_string(),
+        };
+    }
+    for next in last.0 + 1..=round {
+        history.goto_round(next);
+        if history.djungle().overflowed() {
+            history.seek(last);
+            return format!(
+                "Stopped at round {}, round {} overflows a u128",
+                last.0, next
+            );
+        }
+        last = history.djungle().position();
+    }
+    format!("Went to round {}", round)
+}
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "input.txt".to_string());
+    let input = std::fs::read_to_string(&path).unwrap();
+    let mut history = Djungle::<Level>::parse(input).with_history();
+
+    let stdin = io::stdin();
+    let mut last = format!("Loaded {}, h for help", path);
+    loop {
+        // clear the screen and start at the top
+        print!("\x1b[2J\x1b[H{}> ", render(history.djungle(), &last));
+        io::stdout().flush().unwrap();
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 {
+            break;
+        }
+        let mut words = line.split_whitespace();
+        let before = history.djungle().position();
+        last = match (words.next(), words.next()) {
+            (None, _) | (Some("s"), _) => describe(history.step()),
+            (Some("t"), _) => finish_turn(&mut history),
+            (Some("r"), _) => {
+                history.round();
+                format!("Played round {}", history.djungle().played)
+            }
+            (Some("g"), Some(round)) => match round.parse::<usize>() {
+                Ok(round) => go_to(&mut history, round),
+                Err(e) => format!("Not a round: {}", e),
+            },
+            (Some("b"), _) => {
+                if history.step_back() {
+                    "Stepped back".to_string()
+                } else {
+                    "Nothing to step back to".to_string()
+                }
+            }
+            (Some("p"), _) => {
+                let relief = match history.djungle().relief() {
+                    Relief::DivideByThree => Relief::None,
+                    Relief::None => Relief::DivideByThree,
+                };
+                match history.set_relief(relief) {
+                    Ok(()) => format!("Relief is now {:?}", relief),
+                    Err(e) => e,
+                }
+            }
+            (Some("h"), _) => HELP.to_string(),
+            (Some("q"), _) => break,
+            (Some(command), _) => format!("Unknown command {}, h for help", command),
+        };
+        if history.djungle().overflowed() {
+            history.seek(before);
+            last = "That overflows a u128, undone".to_string();
+        }
+    }
+}
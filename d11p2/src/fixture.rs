@@ -0,0 +1,244 @@
+// Writing a jungle down as Rust code
+//
+// For regression tests: parse a real input once, paste the generated function into a test module
+// and the test no longer needs the file. The function returns a `Djungle` (with the default worry
+// type) and can be written with the builder, the `monkeys!` macro or plain struct literals, which
+// need `Item`, `Monkey`, `Operation`, `OperationParameter`, `OperationType` and `Test` in scope.
+
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+use itertools::Itertools;
+
+use crate::{Monkey, OperationParameter, OperationType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FixtureStyle {
+    /// `Djungle::builder().monkey(|m| ...)`
+    #[default]
+    Builder,
+    /// `monkeys! { 0: [79, 98] old * 19, /23 ? 2 : 3 ... }`
+    Macro,
+    /// `Djungle::new(vec![Monkey { ... }])`
+    Literals,
+}
+
+impl fmt::Display for FixtureStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FixtureStyle::Builder => "builder",
+            FixtureStyle::Macro => "macro",
+            FixtureStyle::Literals => "literals",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for FixtureStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "builder" => Ok(FixtureStyle::Builder),
+            "macro" => Ok(FixtureStyle::Macro),
+            "literals" => Ok(FixtureStyle::Literals),
+            _ => Err(format!("unknown fixture style: {}", s)),
+        }
+    }
+}
+
+/// A function called `name` that returns the jungle.
+pub fn fixture_code(monkeys: &[Monkey], name: &str, style: FixtureStyle) -> String {
+    let mut code = format!("fn {}() -> Djungle {{\n", name);
+    let items = |m: &Monkey| m.items.iter().map(|i| i.worry_level).join(", ");
+    match style {
+        FixtureStyle::Builder => {
+            code.push_str("    Djungle::builder()\n");
+            for monkey in monkeys {
+                let operation = match (&monkey.operation.operation, &monkey.operation.right) {
+                    (OperationType::Add, OperationParameter::Old) => "double()".to_string(),
+                    (OperationType::Multiply, OperationParameter::Old) => "square()".to_string(),
+                    (OperationType::Add, OperationParameter::Constant(c)) => format!("add({})", c),
+                    (OperationType::Multiply, OperationParameter::Constant(c)) => {
+                        format!("mul({})", c)
+                    }
+                };
+                writeln!(
+                    code,
+                    "        .monkey(|m| m.items([{}]).{}.divisible_by({}).throw({}, {}))",
+                    items(monkey),
+                    operation,
+                    monkey.test.divisor,
+                    monkey.test.true_target,
+                    monkey.test.false_target
+                )
+                .unwrap();
+            }
+            code.push_str("        .build()\n        .unwrap()\n");
+        }
+        FixtureStyle::Macro => {
+            code.push_str("    monkeys! {\n");
+            for monkey in monkeys {
+                // "new = old * 19" without the "new = "
+                let operation = monkey.operation.to_string();
+                writeln!(
+                    code,
+                    "        {}: [{}] {}, /{} ? {} : {}",
+                    monkey.id,
+                    items(monkey),
+                    operation.trim_start_matches("new = "),
+                    monkey.test.divisor,
+                    monkey.test.true_target,
+                    monkey.test.false_target
+                )
+                .unwrap();
+            }
+            code.push_str("    }\n    .unwrap()\n");
+        }
+        FixtureStyle::Literals => {
+            code.push_str("    Djungle::new(vec![\n");
+            for monkey in monkeys {
+                let operation = match monkey.operation.operation {
+                    OperationType::Add => "Add",
+                    OperationType::Multiply => "Multiply",
+                };
+                let right = match monkey.operation.right {
+                    OperationParameter::Old => "Old".to_string(),
+                    OperationParameter::Constant(c) => format!("Constant({})", c),
+                };
+                let items = monkey
+                    .items
+                    .iter()
+                    .map(|i| format!("Item {{ worry_level: {} }}", i.worry_level))
+                    .join(", ");
+                writeln!(
+                    code,
+                    "        Monkey {{
+            id: {},
+            items: vec![{}],
+            inspections: 0,
+            operation: Operation {{
+                operation: OperationType::{},
+                right: OperationParameter::{},
+            }},
+            test: Test {{
+                divisor: {},
+                true_target: {},
+                false_target: {},
+            }},
+        }},",
+                    monkey.id,
+                    items,
+                    operation,
+                    right,
+                    monkey.test.divisor,
+                    monkey.test.true_target,
+                    monkey.test.false_target
+                )
+                .unwrap();
+            }
+            code.push_str("    ])\n");
+        }
+    }
+    code.push_str("}\n");
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        monkeys, to_input, Djungle, Item, Operation, OperationParameter, OperationType, Parsable,
+        Test,
+    };
+
+    // test.txt in every style, `builder_fixture()` and so on, written by build.rs
+    include!(concat!(env!("OUT_DIR"), "/fixture_code.rs"));
+
+    fn test_monkeys() -> Vec<Monkey> {
+        Vec::<Monkey>::parse(include_str!("../test.txt").to_string())
+    }
+
+    #[test]
+    fn test_generated_code_is_the_input() {
+        for djungle in [builder_fixture(), macro_fixture(), literals_fixture()] {
+            assert_eq!(to_input(&djungle.monkeys), include_str!("../test.txt"));
+        }
+    }
+
+    #[test]
+    fn test_builder_code() {
+        // the same as the fixture in lib.rs
+        let code = fixture_code(&test_monkeys(), "djungle_fixture", FixtureStyle::Builder);
+        assert_eq!(
+            code,
+            "fn djungle_fixture() -> Djungle {
+    Djungle::builder()
+        .monkey(|m| m.items([79, 98]).mul(19).divisible_by(23).throw(2, 3))
+        .monkey(|m| m.items([54, 65, 75, 74]).add(6).divisible_by(19).throw(2, 0))
+        .monkey(|m| m.items([79, 60, 97]).square().divisible_by(13).throw(1, 3))
+        .monkey(|m| m.items([74]).add(3).divisible_by(17).throw(0, 1))
+        .build()
+        .unwrap()
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_macro_code() {
+        let code = fixture_code(&test_monkeys(), "fixture", FixtureStyle::Macro);
+        assert_eq!(
+            code,
+            "fn fixture() -> Djungle {
+    monkeys! {
+        0: [79, 98] old * 19, /23 ? 2 : 3
+        1: [54, 65, 75, 74] old + 6, /19 ? 2 : 0
+        2: [79, 60, 97] old * old, /13 ? 1 : 3
+        3: [74] old + 3, /17 ? 0 : 1
+    }
+    .unwrap()
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_literal_code() {
+        let code = fixture_code(&test_monkeys()[2..3], "fixture", FixtureStyle::Literals);
+        assert_eq!(
+            code,
+            "fn fixture() -> Djungle {
+    Djungle::new(vec![
+        Monkey {
+            id: 2,
+            items: vec![Item { worry_level: 79 }, Item { worry_level: 60 }, Item { worry_level: 97 }],
+            inspections: 0,
+            operation: Operation {
+                operation: OperationType::Multiply,
+                right: OperationParameter::Old,
+            },
+            test: Test {
+                divisor: 13,
+                true_target: 1,
+                false_target: 3,
+            },
+        },
+    ])
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_style_names() {
+        for style in [
+            FixtureStyle::Builder,
+            FixtureStyle::Macro,
+            FixtureStyle::Literals,
+        ] {
+            assert_eq!(style.to_string().parse::<FixtureStyle>(), Ok(style));
+        }
+        assert!("yaml".parse::<FixtureStyle>().is_err());
+    }
+}
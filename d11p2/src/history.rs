@@ -0,0 +1,326 @@
+// Going back in time
+//
+// The simulation can't be run backwards (dividing by three and reducing throw information away),
+// so instead `History` plays the jungle it owns and keeps snapshots: one every `INTERVAL` rounds,
+// taken when a round or step starts there, and one whenever the relief is changed. To go to an
+// earlier point we restore the last snapshot before it and replay forward, stepping through the
+// last (partial) round. A plain jungle doesn't pay for any of this, `with_history` opts in.
+//
+// A point in time is (rounds played, inspections done in the current round). Going forward past a
+// snapshot, by seeking or by playing on after going back, takes its relief as well, so a run that
+// had its relief changed halfway is replayed the way it happened.
+
+use crate::{Djungle, Inspection, Monkey, NumT, Relief, Worry};
+
+const INTERVAL: usize = 100;
+
+/// A jungle that can go back to where it has been.
+#[derive(Debug, Clone)]
+pub struct History<W: Worry = NumT> {
+    djungle: Djungle<W>,
+    // sorted by position
+    snapshots: Vec<Snapshot<W>>,
+}
+
+#[derive(Debug, Clone)]
+struct Snapshot<W: Worry> {
+    position: (usize, usize),
+    turn: usize,
+    relief: Relief,
+    monkeys: Vec<Monkey<W>>,
+}
+
+impl<W: Worry> Djungle<W> {
+    /// Keeps snapshots from here on, so the jungle can go back.
+    pub fn with_history(self) -> History<W> {
+        History {
+            djungle: self,
+            snapshots: Vec::new(),
+        }
+    }
+}
+
+impl<W: Worry> History<W> {
+    pub fn djungle(&self) -> &Djungle<W> {
+        &self.djungle
+    }
+
+    pub fn into_inner(self) -> Djungle<W> {
+        self.djungle
+    }
+
+    /// Same as `Djungle::round`, remembering where it started.
+    pub fn round(&mut self) {
+        let (position, next) = (self.djungle.position(), (self.djungle.played + 1, 0));
+        // the relief changed in the middle of this round, at that very inspection
+        if self
+            .snapshots
+            .iter()
+            .any(|s| s.position > position && s.position < next)
+        {
+            while self.step().is_some() {}
+            return;
+        }
+        self.checkpoint();
+        self.djungle.round();
+    }
+
+    /// Same as `Djungle::step`, remembering where it started.
+    pub fn step(&mut self) -> Option<Inspection<W>> {
+        self.checkpoint();
+        self.djungle.step()
+    }
+
+    // called whenever the simulation moves forward
+    fn checkpoint(&mut self) {
+        let position = self.djungle.position();
+        match self
+            .snapshots
+            .binary_search_by_key(&position, |s| s.position)
+        {
+            // been here before, maybe with another relief than we have now
+            Ok(i) => self.djungle.relief = self.snapshots[i].relief,
+            Err(_) if self.djungle.steps == 0 && self.djungle.played.is_multiple_of(INTERVAL) => {
+                self.snapshot()
+            }
+            Err(_) => {}
+        }
+    }
+
+    fn snapshot(&mut self) {
+        let djungle = &self.djungle;
+        let position = djungle.position();
+        if let Err(i) = self
+            .snapshots
+            .binary_search_by_key(&position, |s| s.position)
+        {
+            self.snapshots.insert(
+                i,
+                Snapshot {
+                    position,
+                    turn: djungle.turn,
+                    relief: djungle.relief(),
+                    monkeys: djungle.monkeys.clone(),
+                },
+            );
+        }
+    }
+
+    /// Changes the relief from now on, keeping the history consistent. Fails if `W` can't do
+    /// the relief, see `Djungle::try_with_relief`.
+    pub fn set_relief(&mut self, relief: Relief) -> Result<(), String> {
+        if relief == self.djungle.relief() {
+            return Ok(());
+        }
+        if relief == Relief::DivideByThree {
+            W::can_divide_by_three()?;
+        }
+        // whatever was recorded after this point didn't happen with this relief
+        let position = self.djungle.position();
+        self.snapshots.retain(|s| s.position < position);
+        self.djungle.relief = relief;
+        self.snapshot();
+        Ok(())
+    }
+
+    /// Goes to the given position (see `Djungle::position`), as recorded in the history. Returns
+    /// false if the history doesn't go back that far, or if that many inspections don't happen in
+    /// the round.
+    pub fn seek(&mut self, target: (usize, usize)) -> bool {
+        let position = self.djungle.position();
+        let restore = self.snapshots.iter().rev().find(|s| s.position <= target);
+        match restore {
+            Some(snapshot) if target < position || snapshot.position > position => {
+                let djungle = &mut self.djungle;
+                djungle.monkeys = snapshot.monkeys.clone();
+                (djungle.played, djungle.steps) = snapshot.position;
+                djungle.turn = snapshot.turn;
+                djungle.relief = snapshot.relief;
+            }
+            None if target < position => return false,
+            _ => {}
+        }
+        while self.djungle.position() < target {
+            if self.djungle.played < target.0 {
+                self.round();
+            } else if self.step().is_none() {
+                // the round ended before the target
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Goes to the start of the given round, backwards or forwards.
+    pub fn goto_round(&mut self, round: usize) -> bool {
+        self.seek((round, 0))
+    }
+
+    /// Undoes the last inspection. Returns false if there's nothing to undo.
+    pub fn step_back(&mut self) -> bool {
+        let (played, steps) = self.djungle.position();
+        if steps > 0 {
+            return self.seek((played, steps - 1));
+        }
+        if played == 0 {
+            return false;
+        }
+        // we don't know how many inspections the last round had without going back there
+        let total = |djungle: &Djungle<W>| djungle.monkeys.iter().map(|m| m.inspections).sum();
+        let after: NumT = total(&self.djungle);
+        if !self.goto_round(played - 1) {
+            return false;
+        }
+        let inspections = (after - total(&self.djungle)) as usize;
+        self.seek((played - 1, inspections.saturating_sub(1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parsable;
+
+    fn test_djungle() -> Djungle<u64> {
+        Djungle::parse(include_str!("../test.txt").to_string())
+    }
+
+    fn test_history() -> History<u64> {
+        test_djungle().with_history()
+    }
+
+    fn state(djungle: &Djungle<u64>) -> Vec<(Vec<u64>, NumT)> {
+        djungle
+            .monkeys
+            .iter()
+            .map(|m| {
+                (
+                    m.items.iter().map(|i| i.worry_level).collect(),
+                    m.inspections,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_goto_round() {
+        let mut history = test_history();
+        let mut states = vec![];
+        for _ in 0..=350 {
+            states.push(state(history.djungle()));
+            history.round();
+        }
+        for round in [0, 349, 100, 1, 250, 99, 350, 201] {
+            assert!(history.goto_round(round));
+            assert_eq!(history.djungle().position(), (round, 0));
+            assert_eq!(state(history.djungle()), states[round]);
+        }
+        // forward to where we've never been
+        assert!(history.goto_round(400));
+        let mut fresh = test_djungle();
+        for _ in 0..400 {
+            fresh.round();
+        }
+        assert_eq!(state(history.djungle()), state(&fresh));
+    }
+
+    #[test]
+    fn test_step_back() {
+        let mut history = test_history();
+        assert!(!history.step_back());
+        let mut states = vec![];
+        // a bit more than two rounds
+        for _ in 0..60 {
+            let djungle = history.djungle();
+            states.push((djungle.position(), state(djungle)));
+            if history.step().is_none() {
+                // round ends aren't steps
+                states.pop();
+                let djungle = history.djungle();
+                states.push((djungle.position(), state(djungle)));
+                history.step();
+            }
+        }
+        while let Some((position, expected)) = states.pop() {
+            assert!(history.step_back());
+            assert_eq!(history.djungle().position(), position);
+            assert_eq!(state(history.djungle()), expected);
+        }
+        assert!(!history.step_back());
+    }
+
+    #[test]
+    fn test_relief_change_is_replayed() {
+        let mut history = test_djungle()
+            .with_relief(Relief::DivideByThree)
+            .with_history();
+        for _ in 0..5 {
+            history.round();
+        }
+        history.set_relief(Relief::None).unwrap();
+        for _ in 0..5 {
+            history.round();
+        }
+        let expected = state(history.djungle());
+        assert!(history.goto_round(3));
+        assert_eq!(history.djungle().relief(), Relief::DivideByThree);
+        assert!(history.goto_round(10));
+        assert_eq!(history.djungle().relief(), Relief::None);
+        assert_eq!(state(history.djungle()), expected);
+    }
+
+    #[test]
+    fn test_relief_change_is_played_again() {
+        let mut history = test_djungle()
+            .with_relief(Relief::DivideByThree)
+            .with_history();
+        for _ in 0..5 {
+            history.round();
+        }
+        history.set_relief(Relief::None).unwrap();
+        history.round();
+        let round_6 = state(history.djungle());
+        for _ in 0..4 {
+            history.round();
+        }
+        // playing on from an earlier round takes the relief that was set at round 5 again
+        assert!(history.goto_round(3));
+        for _ in 0..3 {
+            history.round();
+        }
+        assert_eq!(history.djungle().relief(), Relief::None);
+        assert_eq!(state(history.djungle()), round_6);
+        // and stepping back into round 6 replays it the same way
+        assert!(history.step_back());
+        assert!(history.goto_round(6));
+        assert_eq!(state(history.djungle()), round_6);
+    }
+
+    #[test]
+    fn test_relief_change_within_a_round() {
+        let mut plain = test_djungle().with_relief(Relief::DivideByThree);
+        let mut history = plain.clone().with_history();
+        history.round();
+        plain.round();
+        for _ in 0..4 {
+            history.step();
+            plain.step();
+        }
+        history.set_relief(Relief::None).unwrap();
+        plain = plain.with_relief(Relief::None);
+        for _ in 0..3 {
+            history.round();
+            plain.round();
+        }
+        assert!(history.goto_round(1));
+        for _ in 0..3 {
+            history.round();
+        }
+        assert_eq!(history.djungle().position(), plain.position());
+        assert_eq!(state(history.djungle()), state(&plain));
+        assert!(history.step_back());
+        assert!(history.goto_round(3));
+        history.round();
+        assert_eq!(state(history.djungle()), state(&plain));
+    }
+}
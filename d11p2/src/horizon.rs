@@ -0,0 +1,211 @@
+// How long until a worry level might overflow?
+//
+// Without the mod factor (part 1) worry levels can grow forever if the relief isn't strong enough,
+// so whether a u32 is fine depends on how many rounds we play. Instead of simulating, we track an
+// upper bound of the worry levels every monkey might be holding (interval analysis, the lower
+// bound is always 0 so we only keep the upper one):
+// * a monkey's turn applies its operation to its bound, then the relief,
+// * both possible targets get the result, since we don't know which way the test goes,
+// * the monkey itself is empty afterwards, but might get items from monkeys after it.
+// A round is unsafe as soon as any of those bounds doesn't fit into the backend.
+
+use std::collections::HashSet;
+
+use crate::monkey::{mod_factor, Monkey, NumT, Relief};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Horizon {
+    /// Overflow becomes possible in this round (counting from 1).
+    Overflow(usize),
+    /// The bounds repeat before anything can overflow, so no round ever will.
+    Never,
+    /// Nothing overflows in the given number of rounds, but it might afterwards.
+    Beyond(usize),
+}
+
+/// Upper bounds of the worry levels each monkey holds, round after round. Stops once a bound
+/// doesn't even fit into a u128 anymore.
+pub struct RoundBounds<'a> {
+    monkeys: &'a [Monkey],
+    relief: Relief,
+    mod_factor: Option<NumT>,
+    // `None` if the monkey can't be holding anything
+    holding: Vec<Option<NumT>>,
+    overflowed: bool,
+}
+
+impl<'a> RoundBounds<'a> {
+    pub fn new(monkeys: &'a [Monkey], relief: Relief) -> Self {
+        RoundBounds {
+            monkeys,
+            relief,
+            mod_factor: mod_factor(monkeys),
+            holding: monkeys
+                .iter()
+                .map(|m| m.items.iter().map(|i| i.worry_level).max())
+                .collect(),
+            overflowed: false,
+        }
+    }
+
+    fn turn(&mut self, id: usize, monkey: &Monkey) -> Option<Option<NumT>> {
+        let Some(old) = self.holding[id].take() else {
+            // bored monkey
+            return Some(None);
+        };
+        let old = match (self.relief, self.mod_factor) {
+            (Relief::None, Some(m)) => old.min(m - 1),
+            _ => old,
+        };
+        let new = monkey.operation.worst_case(old)?;
+        let thrown = match self.relief {
+            Relief::DivideByThree => new / 3,
+            Relief::None => new,
+        };
+        for target in [monkey.test.true_target, monkey.test.false_target] {
+            self.holding[target] = Some(self.holding[target].map_or(thrown, |h| h.max(thrown)));
+        }
+        Some(Some(new))
+    }
+}
+
+impl Iterator for RoundBounds<'_> {
+    /// The largest worry level any item might have at any point during the round.
+    type Item = NumT;
+
+    fn next(&mut self) -> Option<NumT> {
+        if self.overflowed {
+            return None;
+        }
+        let mut largest = self.holding.iter().flatten().copied().max().unwrap_or(0);
+        for (id, monkey) in self.monkeys.iter().enumerate() {
+            match self.turn(id, monkey) {
+                Some(new) => largest = largest.max(new.unwrap_or(0)),
+                None => {
+                    self.overflowed = true;
+                    return None;
+                }
+            }
+        }
+        Some(largest)
+    }
+}
+
+/// The first of `rounds` rounds in which a worry level might no longer fit into `bits` bits.
+pub fn overflow_horizon(monkeys: &[Monkey], relief: Relief, bits: u32, rounds: usize) -> Horizon {
+    let max = NumT::MAX >> (NumT::BITS - bits);
+    let mut bounds = RoundBounds::new(monkeys, relief);
+    let mut seen = HashSet::new();
+    for round in 1..=rounds {
+        if !seen.insert(bounds.holding.clone()) {
+            return Horizon::Never;
+        }
+        match bounds.next() {
+            Some(largest) if largest <= max => continue,
+            _ => return Horizon::Overflow(round),
+        }
+    }
+    Horizon::Beyond(rounds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Djungle, Observer, Overflowing, Parsable, Throw, Worry};
+
+    fn monkeys(input: &str) -> Vec<Monkey> {
+        Vec::<Monkey>::parse(input.to_string())
+    }
+
+    #[test]
+    fn test_bounds_cover_simulation() {
+        let monkeys = monkeys(include_str!("../input.txt"));
+        let mut dj: Djungle<u64> =
+            Djungle::lift(monkeys.clone()).with_relief(Relief::DivideByThree);
+        for bound in RoundBounds::new(&monkeys, Relief::DivideByThree).take(20) {
+            dj.round();
+            let actual = dj
+                .monkeys
+                .iter()
+                .flat_map(|m| m.items.iter())
+                .map(|i| i.worry_level as NumT)
+                .max()
+                .unwrap();
+            assert!(actual <= bound);
+        }
+    }
+
+    // remembers the first throw of an item whose worry level overflowed
+    struct FirstOverflow {
+        round: usize,
+        found: Option<(usize, usize)>,
+    }
+
+    impl<W> Observer<Overflowing<W>> for FirstOverflow
+    where
+        Overflowing<W>: Worry,
+    {
+        fn throw(&mut self, from: usize, throw: &Throw<Overflowing<W>>) {
+            if self.found.is_none() && throw.item.worry_level.overflowed {
+                self.found = Some((self.round, from));
+            }
+        }
+    }
+
+    // (round, monkey) of the first real overflow
+    fn first_overflow<W>(monkeys: &[Monkey], rounds: usize) -> Option<(usize, usize)>
+    where
+        Overflowing<W>: Worry,
+    {
+        let mut dj: Djungle<Overflowing<W>> =
+            Djungle::lift(monkeys.to_vec()).with_relief(Relief::DivideByThree);
+        let mut first = FirstOverflow {
+            round: 0,
+            found: None,
+        };
+        for round in 1..=rounds {
+            first.round = round;
+            dj.round_observed(&mut first);
+        }
+        first.found
+    }
+
+    #[test]
+    fn test_divide_by_three_horizon() {
+        let monkeys = monkeys(include_str!("../test.txt"));
+        let relief = Relief::DivideByThree;
+        // the bounds assume every item takes the worst way, monkey 2 squaring it every round
+        assert_eq!(
+            overflow_horizon(&monkeys, relief, 32, 1000),
+            Horizon::Overflow(2)
+        );
+        assert_eq!(
+            overflow_horizon(&monkeys, relief, 64, 1000),
+            Horizon::Overflow(3)
+        );
+        assert_eq!(
+            overflow_horizon(&monkeys, relief, 32, 1),
+            Horizon::Beyond(1)
+        );
+        // the real items take a lot longer, but it's still monkey 2 squaring them
+        assert_eq!(first_overflow::<u32>(&monkeys, 1000), Some((28, 2)));
+        assert_eq!(first_overflow::<u64>(&monkeys, 1000), Some((92, 2)));
+        // nothing overflows before the horizon
+        assert_eq!(first_overflow::<u32>(&monkeys, 2), None);
+        assert_eq!(first_overflow::<u64>(&monkeys, 3), None);
+    }
+
+    #[test]
+    fn test_modulo_never_overflows() {
+        let monkeys = monkeys(include_str!("../test.txt"));
+        assert_eq!(
+            overflow_horizon(&monkeys, Relief::None, 64, 10000),
+            Horizon::Never
+        );
+        // the starting items are small, it's squaring the reduced ones from round 2 on that hurts
+        assert_eq!(
+            overflow_horizon(&monkeys, Relief::None, 32, 10000),
+            Horizon::Overflow(2)
+        );
+    }
+}
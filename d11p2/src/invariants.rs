@@ -0,0 +1,273 @@
+// Checking the jungle while it runs
+//
+// `Checked` plays rounds like the jungle it wraps and looks at the result after every round:
+// * no item got lost or duplicated,
+// * no monkey's inspection count went down,
+// * in modulo mode every worry level is at most what one operation makes of a level below the mod
+//   factor (we reduce before the operation, so "below the mod factor" itself doesn't hold),
+// * for the first rounds, every worry level has the same residue modulo every divisor as the real,
+//   never reduced worry level, which we keep next to it as a big number.
+// The shadow grows fast (squaring doubles its length), so only keep it for a few rounds.
+
+use std::fmt;
+
+use num_bigint::BigUint;
+
+use crate::{mod_factor, Djungle, NumT, Relief, Worry};
+
+/// What went wrong, and where.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// The round that was just played, starting at 1.
+    pub round: usize,
+    /// The monkey it's about, unless it's about all of them.
+    pub monkey: Option<usize>,
+    /// The position of the item in the monkey's queue, if it's about a single item.
+    pub item: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "round {}", self.round)?;
+        if let Some(monkey) = self.monkey {
+            write!(f, ", monkey {}", monkey)?;
+        }
+        if let Some(item) = self.item {
+            write!(f, ", item {}", item)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+pub struct Checked<W: Worry = NumT> {
+    pub djungle: Djungle<W>,
+    // the unreduced worry levels, for as long as we keep them
+    shadow: Option<Vec<Vec<BigUint>>>,
+    shadow_rounds: usize,
+    items: usize,
+    inspections: Vec<NumT>,
+    // the largest worry level modulo mode can leave behind
+    bound: Option<NumT>,
+}
+
+impl<W: Worry + Into<NumT>> Checked<W> {
+    /// Checks every round, and against the unreduced worry levels for the first `shadow_rounds`.
+    pub fn new(djungle: Djungle<W>, shadow_rounds: usize) -> Self {
+        assert_eq!(djungle.steps, 0, "start checking at the start of a round");
+        let shadow = djungle
+            .monkeys
+            .iter()
+            .map(|m| {
+                m.items
+                    .iter()
+                    .map(|i| BigUint::from(i.worry_level.clone().into()))
+                    .collect()
+            })
+            .collect();
+        // no bound if it doesn't fit, the worry levels can't be that large anyway
+        let bound = mod_factor(&djungle.monkeys)
+            .and_then(|mod_factor| {
+                djungle
+                    .monkeys
+                    .iter()
+                    .map(|m| m.operation.worst_case(mod_factor - 1))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .and_then(|bounds| bounds.into_iter().max());
+        Checked {
+            items: djungle.monkeys.iter().map(|m| m.items.len()).sum(),
+            inspections: djungle.monkeys.iter().map(|m| m.inspections).collect(),
+            shadow: Some(shadow),
+            shadow_rounds,
+            bound,
+            djungle,
+        }
+    }
+
+    /// Plays a round, then checks it.
+    pub fn round(&mut self) -> Result<(), Violation> {
+        self.djungle.round();
+        self.shadow_round();
+        self.check()
+    }
+
+    fn shadow_round(&mut self) {
+        if self.djungle.played > self.shadow_rounds {
+            self.shadow = None;
+        }
+        let Some(shadow) = self.shadow.as_mut() else {
+            return;
+        };
+        for (id, monkey) in self.djungle.monkeys.iter().enumerate() {
+            for old in std::mem::take(&mut shadow[id]) {
+                let new = monkey.operation.evaluate(&old, &());
+                let new = match self.djungle.relief() {
+                    Relief::DivideByThree => new / 3u32,
+                    Relief::None => new,
+                };
+                let target = if new.is_divisible_by(monkey.test.divisor, &()) {
+                    monkey.test.true_target
+                } else {
+                    monkey.test.false_target
+                };
+                shadow[target].push(new);
+            }
+        }
+    }
+
+    fn check(&mut self) -> Result<(), Violation> {
+        let round = self.djungle.played;
+        let violation = |monkey, item, message: String| Violation {
+            round,
+            monkey,
+            item,
+            message,
+        };
+
+        let items = self
+            .djungle
+            .monkeys
+            .iter()
+            .map(|m| m.items.len())
+            .sum::<usize>();
+        // somebody lost or made up an item, but we can't tell who
+        if items != self.items {
+            return Err(violation(
+                None,
+                None,
+                format!("there are {} items, there were {}", items, self.items),
+            ));
+        }
+
+        for (id, monkey) in self.djungle.monkeys.iter().enumerate() {
+            if monkey.inspections < self.inspections[id] {
+                return Err(violation(
+                    Some(id),
+                    None,
+                    format!(
+                        "inspections went down from {} to {}",
+                        self.inspections[id], monkey.inspections
+                    ),
+                ));
+            }
+            self.inspections[id] = monkey.inspections;
+
+            for (position, item) in monkey.items.iter().enumerate() {
+                let level: NumT = item.worry_level.clone().into();
+                if let (Relief::None, Some(bound)) = (self.djungle.relief(), self.bound) {
+                    if level > bound {
+                        return Err(violation(
+                            Some(id),
+                            Some(position),
+                            format!("worry level {} is above {}", level, bound),
+                        ));
+                    }
+                }
+                let Some(shadow) = self.shadow.as_ref() else {
+                    continue;
+                };
+                let Some(real) = shadow[id].get(position) else {
+                    return Err(violation(
+                        Some(id),
+                        Some(position),
+                        "the unreduced run doesn't have this item here".to_string(),
+                    ));
+                };
+                for divisor in self.djungle.monkeys.iter().map(|m| m.test.divisor) {
+                    if BigUint::from(level % divisor) != real % divisor {
+                        return Err(violation(
+                            Some(id),
+                            Some(position),
+                            format!(
+                                "worry level {} is {} modulo {}, the unreduced one is {}",
+                                level,
+                                level % divisor,
+                                divisor,
+                                real % divisor
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parsable;
+
+    fn checked(input: &str, relief: Relief) -> Checked<u64> {
+        Checked::new(Djungle::parse(input.to_string()).with_relief(relief), 10)
+    }
+
+    #[test]
+    fn test_clean_runs() {
+        for input in [include_str!("../test.txt"), include_str!("../input.txt")] {
+            let mut part2 = checked(input, Relief::None);
+            for _ in 0..1000 {
+                part2.round().unwrap();
+            }
+            let mut part1 = checked(input, Relief::DivideByThree);
+            for _ in 0..20 {
+                part1.round().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_lost_item() {
+        let mut checked = checked(include_str!("../test.txt"), Relief::None);
+        checked.round().unwrap();
+        checked.djungle.monkeys[1].items.pop();
+        let violation = checked.check().unwrap_err();
+        assert_eq!((violation.round, violation.monkey), (1, None));
+        assert_eq!(
+            violation.to_string(),
+            "round 1: there are 9 items, there were 10"
+        );
+    }
+
+    #[test]
+    fn test_inspections_went_down() {
+        let mut checked = checked(include_str!("../test.txt"), Relief::None);
+        checked.round().unwrap();
+        checked.djungle.monkeys[2].inspections -= 1;
+        let violation = checked.check().unwrap_err();
+        assert_eq!((violation.monkey, violation.item), (Some(2), None));
+    }
+
+    #[test]
+    fn test_wrong_residue() {
+        let mut checked = checked(include_str!("../test.txt"), Relief::None);
+        checked.round().unwrap();
+        checked.djungle.monkeys[0].items[1].worry_level += 1;
+        let violation = checked.check().unwrap_err();
+        assert_eq!((violation.monkey, violation.item), (Some(0), Some(1)));
+        assert!(violation.message.contains("modulo"), "{}", violation);
+    }
+
+    #[test]
+    fn test_worry_above_bound() {
+        let mut checked = checked(include_str!("../test.txt"), Relief::None);
+        checked.round().unwrap();
+        checked.djungle.monkeys[1].items[2].worry_level = u64::MAX;
+        let violation = checked.check().unwrap_err();
+        assert_eq!((violation.monkey, violation.item), (Some(1), Some(2)));
+        assert!(violation.message.contains("above"), "{}", violation);
+    }
+
+    #[test]
+    fn test_display() {
+        let violation = Violation {
+            round: 3,
+            monkey: Some(1),
+            item: Some(2),
+            message: "gone".to_string(),
+        };
+        assert_eq!(violation.to_string(), "round 3, monkey 1, item 2: gone");
+    }
+}
@@ -0,0 +1,486 @@
+//
+// insert were items gorillas meme here
+//
+// Desired output: monkey business
+//
+// monkey business is calculated by multiplying the inspections of the two most active monkeys over
+// 20 rounds of chaos.
+//
+// A round of the monkey business goes like this:
+// 1. Inspect an item
+// 2. Be relieved that the monkey didn't break your stuff: floor(divide worry level by 3)
+// 3. Monkey tests your worry level and throws all items according to the test
+//
+// Notes:
+// * Monkeys act in the order given in the input
+// * Thrown items go on the end of the monkeys item list
+// * Empty monkey be bored and doesn't do anything (but note that a monkey might get an item during
+//   a round)
+// * The number of monkeys is not fixed
+//
+// Simplifications:
+// * There's no need to track the inspections per item, only the inspections per monkey
+// * Tests are only whether the worry level is divisible by a number
+// * an operation always starts with the old value
+// * positive vibes (read integers) only
+//
+// Complications:
+// * Operations can reference the old value twice (e.g. Operation: new = old * old)
+// * worry levels are now not divided by 3 anymore
+//
+// Solution:
+// * by multiplying all the divisors of the tests, we get a number that is divisible by all the
+//   divisors of the tests and thus we can use the modulo operator on the worry levels with this
+//   number while mathematically getting the same result as without the modulo operator
+// * we need to use a larger type than u32 still though
+// * how a worry level is stored is up to the `Worry` implementation, see `worry.rs`
+
+use itertools::Itertools;
+
+mod affine;
+mod arena;
+mod backend;
+mod barrett;
+mod batch;
+mod breakpoint;
+mod builder;
+mod compile;
+mod fixture;
+mod history;
+mod horizon;
+mod invariants;
+mod macros;
+mod minimise;
+mod monkey;
+mod multiset;
+mod overflowing;
+mod regression;
+mod residue;
+mod rounds;
+mod states;
+mod throwlog;
+mod tracediff;
+mod worry;
+
+pub use affine::{skips, Affine, Skip};
+pub use arena::Arena;
+pub use backend::{Backend, Simulation};
+pub use barrett::{Barrett, BarrettModulus};
+pub use batch::{Batch, Lane};
+pub use breakpoint::{Breakpoint, Debugger, Hit};
+pub use builder::{DjungleBuilder, MonkeyBuilder};
+pub use compile::{CompiledDjungle, CompiledMonkey, CompiledOperation, CompiledTest};
+pub use fixture::{fixture_code, FixtureStyle};
+pub use history::History;
+pub use horizon::{overflow_horizon, Horizon, RoundBounds};
+pub use invariants::{Checked, Violation};
+pub use minimise::{minimise, to_input, Reproducer};
+pub use monkey::{
+    mod_factor, worst_case, Item, Monkey, NumT, Operation, OperationParameter, OperationType,
+    Parsable, Relief, Test,
+};
+pub use multiset::Multiset;
+pub use overflowing::Overflowing;
+pub use regression::{check_fixture, Expected};
+pub use residue::Residues;
+pub use rounds::{Event, Events, Inspection, RoundReport, Rounds};
+pub use states::{Orbit, State, StateSpace};
+pub use throwlog::{Mismatch, ThrowLog, ThrowRecord};
+pub use tracediff::{first_divergence, Divergence};
+pub use worry::Worry;
+
+#[derive(Debug, Clone)]
+pub struct Djungle<W: Worry = NumT> {
+    pub monkeys: Vec<Monkey<W>>,
+    pub mod_factor: W::Modulus,
+    relief: Relief,
+    /// How many rounds have been played.
+    pub played: usize,
+    /// Whose turn it is in the current round, only moves on its own when stepping.
+    pub turn: usize,
+    /// How many inspections have been done in the current round, when stepping.
+    pub steps: usize,
+}
+impl<W: Worry> Djungle<W> {
+    pub fn new(monkeys: Vec<Monkey<W>>) -> Self {
+        let mod_factor = W::modulus(&Self::divisors(&monkeys)).unwrap_or_else(|e| panic!("{}", e));
+        Self::with_mod_factor(monkeys, mod_factor)
+    }
+
+    fn with_mod_factor(monkeys: Vec<Monkey<W>>, mod_factor: W::Modulus) -> Self {
+        Djungle {
+            monkeys,
+            mod_factor,
+            relief: Relief::default(),
+            played: 0,
+            turn: 0,
+            steps: 0,
+        }
+    }
+
+    pub fn with_relief(self, relief: Relief) -> Self {
+        self.try_with_relief(relief)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as `with_relief`, saying why `W` can't do the relief instead of panicking.
+    pub fn try_with_relief(mut self, relief: Relief) -> Result<Self, String> {
+        if relief == Relief::DivideByThree {
+            W::can_divide_by_three()?;
+        }
+        self.relief = relief;
+        Ok(self)
+    }
+
+    pub fn relief(&self) -> Relief {
+        self.relief
+    }
+
+    /// (rounds played, inspections done in the current round)
+    pub fn position(&self) -> (usize, usize) {
+        (self.played, self.steps)
+    }
+
+    fn divisors(monkeys: &[Monkey<W>]) -> Vec<NumT> {
+        monkeys.iter().map(|m| m.test.divisor).collect()
+    }
+}
+
+impl<W: Worry> Monkey<W> {
+    pub fn inspect_and_throw(&mut self, mod_factor: &W::Modulus, relief: Relief) -> Vec<Throw<W>> {
+        // each item will be inspected
+        self.inspections += self.items.len() as NumT;
+        match relief {
+            Relief::DivideByThree => {
+                self.items
+                    .iter_mut()
+                    .for_each(|i| i.apply_operation(&self.operation, mod_factor));
+                // be relieved that he didn't break the item
+                self.items
+                    .iter_mut()
+                    .for_each(|i| i.worry_level.divide_by_three());
+            }
+            Relief::None => {
+                // keep the worry levels small
+                self.items
+                    .iter_mut()
+                    .for_each(|i| i.worry_level.reduce(mod_factor));
+                self.items
+                    .iter_mut()
+                    .for_each(|i| i.apply_operation(&self.operation, mod_factor));
+            }
+        }
+        // generate throws
+        let throws = self
+            .items
+            .drain(..)
+            .map(|i| self.test.throw(i, mod_factor))
+            .collect();
+        throws
+    }
+
+    pub fn catch(&mut self, item: Item<W>) {
+        self.items.push(item);
+    }
+}
+impl Test {
+    pub fn throw<W: Worry>(&self, item: Item<W>, mod_factor: &W::Modulus) -> Throw<W> {
+        if item.worry_level.is_divisible_by(self.divisor, mod_factor) {
+            Throw {
+                item,
+                target: self.true_target,
+            }
+        } else {
+            Throw {
+                item,
+                target: self.false_target,
+            }
+        }
+    }
+}
+#[derive(Debug, Clone)]
+pub struct Throw<W: Worry = NumT> {
+    pub item: Item<W>,
+    pub target: usize,
+}
+impl Operation {
+    pub fn evaluate<W: Worry>(&self, old: &W, mod_factor: &W::Modulus) -> W {
+        match (&self.operation, &self.right) {
+            (OperationType::Add, OperationParameter::Constant(c)) => {
+                old.add_constant(*c, mod_factor)
+            }
+            (OperationType::Add, OperationParameter::Old) => old.add(old, mod_factor),
+            (OperationType::Multiply, OperationParameter::Constant(c)) => {
+                old.mul_constant(*c, mod_factor)
+            }
+            (OperationType::Multiply, OperationParameter::Old) => old.mul(old, mod_factor),
+        }
+    }
+}
+impl<W: Worry> Item<W> {
+    fn apply_operation(&mut self, operation: &Operation, mod_factor: &W::Modulus) {
+        self.worry_level = operation.evaluate(&self.worry_level, mod_factor);
+    }
+}
+
+impl<W: Worry> Djungle<W> {
+    pub fn monkey_business(&self) -> NumT {
+        self.monkeys
+            .iter()
+            .map(|m| m.inspections)
+            .sorted()
+            .rev()
+            .take(2)
+            .product()
+    }
+
+    pub fn round(&mut self) {
+        self.round_observed(&mut ());
+    }
+
+    /// Same as `round`, telling the observer about every turn and throw.
+    pub fn round_observed(&mut self, observer: &mut impl Observer<W>) {
+        // after stepping, the round is finished from wherever we are
+        for monkey_id in self.turn..self.monkeys.len() {
+            let monkey = &mut self.monkeys[monkey_id];
+            observer.turn(monkey);
+            let throws = monkey.inspect_and_throw(&self.mod_factor, self.relief);
+            for throw in throws {
+                observer.throw(monkey_id, &throw);
+                let target_monkey = &mut self.monkeys[throw.target];
+                target_monkey.catch(throw.item);
+            }
+        }
+        self.turn = 0;
+        self.steps = 0;
+        self.played += 1;
+    }
+}
+
+/// Looks at a round while it's played, see `Djungle::round_observed`.
+pub trait Observer<W: Worry> {
+    /// A monkey is about to inspect all of its items.
+    fn turn(&mut self, _monkey: &Monkey<W>) {}
+    /// It threw one of them, in the order it inspected them.
+    fn throw(&mut self, _from: usize, _throw: &Throw<W>) {}
+}
+
+// nobody's looking
+impl<W: Worry> Observer<W> for () {}
+
+impl<W: Worry> Parsable for Djungle<W> {
+    fn try_parse(input: &str) -> Result<Self, String> {
+        Djungle::try_lift(Vec::<Monkey>::try_parse(input)?)
+    }
+}
+
+impl<W: Worry> Djungle<W> {
+    /// Builds a jungle from monkeys with plain worry levels, converting every item to `W`.
+    pub fn lift(monkeys: Vec<Monkey>) -> Self {
+        Self::try_lift(monkeys).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as `lift`, saying why `W` can't hold the jungle instead of panicking.
+    pub fn try_lift(monkeys: Vec<Monkey>) -> Result<Self, String> {
+        // the worry representation needs to know all divisors before it can store any item
+        let mod_factor = W::modulus(&monkeys.iter().map(|m| m.test.divisor).collect_vec())?;
+        let monkeys = monkeys
+            .into_iter()
+            .map(|m| {
+                Ok(Monkey {
+                    id: m.id,
+                    items: m
+                        .items
+                        .into_iter()
+                        .map(|i| {
+                            Ok(Item {
+                                worry_level: W::try_lift(i.worry_level, &mod_factor)?,
+                            })
+                        })
+                        .collect::<Result<_, String>>()?,
+                    inspections: m.inspections,
+                    operation: m.operation,
+                    test: m.test,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Djungle::with_mod_factor(monkeys, mod_factor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plays test.txt and input.txt on a plain jungle and on whatever `start` makes of another
+    /// copy, letting `check` compare the two after every round.
+    pub(crate) fn assert_same_as_plain<W: Worry, T>(
+        relief: Relief,
+        rounds: usize,
+        start: impl Fn(Djungle<W>) -> T,
+        round: impl Fn(&mut T),
+        check: impl Fn(&Djungle<W>, &T),
+    ) {
+        for input in [include_str!("../test.txt"), include_str!("../input.txt")] {
+            let mut plain: Djungle<W> = Djungle::parse(input.to_string()).with_relief(relief);
+            let mut other = start(Djungle::parse(input.to_string()).with_relief(relief));
+            for _ in 0..rounds {
+                plain.round();
+                round(&mut other);
+                check(&plain, &other);
+            }
+        }
+    }
+
+    /// Every monkey holds the same items and inspected as often.
+    pub(crate) fn assert_same_monkeys<W: Worry>(left: &[Monkey<W>], right: &[Monkey<W>]) {
+        for (l, r) in left.iter().zip(right.iter()) {
+            assert_eq!(l.items, r.items);
+            assert_eq!(l.inspections, r.inspections);
+        }
+    }
+
+    fn djungle_fixture() -> Djungle {
+        Djungle::builder()
+            .monkey(|m| m.items([79, 98]).mul(19).divisible_by(23).throw(2, 3))
+            .monkey(|m| {
+                m.items([54, 65, 75, 74])
+                    .add(6)
+                    .divisible_by(19)
+                    .throw(2, 0)
+            })
+            .monkey(|m| m.items([79, 60, 97]).square().divisible_by(13).throw(1, 3))
+            .monkey(|m| m.items([74]).add(3).divisible_by(17).throw(0, 1))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_first_round_of_monkey_0() {
+        let mut dj = djungle_fixture();
+        dj.round();
+        assert_eq!(
+            dj.monkeys[0].items,
+            [60, 71, 81, 80]
+                .iter()
+                .map(|i| Item {
+                    worry_level: i.to_owned()
+                })
+                .collect::<Vec<Item>>()
+        );
+    }
+    #[test]
+    fn test_first_round_of_monkey_1() {
+        let mut dj = djungle_fixture();
+        dj.round();
+        assert_eq!(
+            dj.monkeys[1].items,
+            [77, 1504, 1865, 6244, 3603, 9412]
+                .iter()
+                .map(|i| Item {
+                    worry_level: i.to_owned()
+                })
+                .collect::<Vec<Item>>()
+        );
+    }
+    #[test]
+    fn test_first_round_of_monkey_2() {
+        let mut dj = djungle_fixture();
+        dj.round();
+        assert_eq!(dj.monkeys[2].items, vec![]);
+    }
+    #[test]
+    fn test_first_round_of_monkey_3() {
+        let mut dj = djungle_fixture();
+        dj.round();
+        assert_eq!(dj.monkeys[3].items, vec![]);
+    }
+
+    #[test]
+    fn test_inspections() {
+        let mut dj = djungle_fixture();
+        let expected_inspections: Vec<NumT> = vec![2, 4, 3, 6];
+        dj.round();
+        let actual_inspections = dj
+            .monkeys
+            .iter()
+            .map(|m| m.inspections)
+            .collect::<Vec<NumT>>();
+        assert_eq!(actual_inspections, expected_inspections);
+    }
+
+    #[test]
+    fn test_monkey_business() {
+        let mut dj = djungle_fixture();
+        for _ in 0..10000 {
+            dj.round();
+        }
+        assert_eq!(dj.monkey_business(), 2713310158)
+    }
+
+    #[test]
+    fn test_monkey_business_divide_by_three() {
+        let mut dj = djungle_fixture().with_relief(Relief::DivideByThree);
+        for _ in 0..20 {
+            dj.round();
+        }
+        assert_eq!(dj.monkey_business(), 10605)
+    }
+
+    #[test]
+    fn test_parse_matches_fixture() {
+        let dj: Djungle = Djungle::parse(include_str!("../test.txt").to_string());
+        let fixture = djungle_fixture();
+        assert_eq!(dj.mod_factor, fixture.mod_factor);
+        for (parsed, expected) in dj.monkeys.iter().zip(fixture.monkeys.iter()) {
+            assert_eq!(parsed.items, expected.items);
+            assert_eq!(parsed.test.divisor, expected.test.divisor);
+        }
+    }
+
+    #[test]
+    fn test_worry_type_too_small() {
+        let input = include_str!("../test.txt");
+        // two divisors of 10^20 make a mod factor of over 10^40, more than even a u128 can hold
+        let big = input
+            .replace("divisible by 23", "divisible by 100000000000000000000")
+            .replace("divisible by 19", "divisible by 100000000000000000000");
+        assert_eq!(
+            Djungle::<u128>::try_parse(&big).unwrap_err(),
+            "mod factor does not fit into a u128"
+        );
+        assert!(Djungle::<Residues>::try_parse(&big).is_err());
+        let one_big = input.replace("divisible by 23", "divisible by 100000000000000000000");
+        assert_eq!(
+            Djungle::<u64>::try_parse(&one_big).unwrap_err(),
+            "mod factor does not fit into a u64"
+        );
+        assert!(Djungle::<u128>::try_parse(&one_big).is_ok());
+        let big_item = input.replace("79, 98", "79, 98000000000000000000");
+        assert_eq!(
+            Djungle::<u64>::try_parse(&big_item).unwrap_err(),
+            "worry level 98000000000000000000 does not fit into a u64"
+        );
+        assert!(Djungle::<u128>::try_parse(&big_item).is_ok());
+    }
+
+    #[test]
+    fn test_relief_needs_worry_levels() {
+        let input = include_str!("../test.txt");
+        assert_eq!(
+            Djungle::<Residues>::parse(input.to_string())
+                .try_with_relief(Relief::DivideByThree)
+                .unwrap_err(),
+            "residue vectors can't be divided by three"
+        );
+        assert!(Djungle::<Barrett>::parse(input.to_string())
+            .try_with_relief(Relief::DivideByThree)
+            .is_err());
+        assert!(Djungle::<Barrett>::parse(input.to_string())
+            .try_with_relief(Relief::None)
+            .is_ok());
+        assert!(Djungle::<u32>::parse(input.to_string())
+            .try_with_relief(Relief::DivideByThree)
+            .is_ok());
+    }
+}
@@ -0,0 +1,96 @@
+// `monkeys!`, a jungle written almost like the puzzle writes it
+//
+//     let djungle: Djungle = monkeys! {
+//         0: [79, 98] old * 19, /23 ? 2 : 3
+//         1: [54, 65, 75, 74] old + 6, /19 ? 2 : 0
+//         2: [79, 60, 97] old * old, /13 ? 1 : 3
+//         3: [74] old + 3, /17 ? 0 : 1
+//     }?;
+//
+// One monkey per line: id, starting items, operation, divisor, where to throw if divisible and
+// where if not. The ids have to count up from 0 like in the input, they're only there to read the
+// targets more easily. Ids out of order, a divisor of 0 and a target that doesn't exist are checked
+// in a const block, so they don't compile. Whether the mod factor and the items fit the worry type
+// depends on the type the jungle ends up with, so that's left to the builder, whose `Result` the
+// macro returns.
+
+/// Builds a `Djungle` from monkeys written like `0: [79, 98] old * 19, /23 ? 2 : 3`, or says why
+/// the worry type can't hold it.
+///
+/// ```compile_fail
+/// // monkey 1 doesn't exist
+/// let djungle: djungle::Djungle = djungle::monkeys! { 0: [1] old * 2, /3 ? 0 : 1 }.unwrap();
+/// ```
+#[macro_export]
+macro_rules! monkeys {
+    ($($id:literal : [$($item:literal),* $(,)?] old $op:tt $right:tt , / $divisor:literal ? $true_target:literal : $false_target:literal $(;)?)*) => {{
+        const {
+            let ids: &[usize] = &[$($id),*];
+            let divisors: &[$crate::NumT] = &[$($divisor),*];
+            let targets: &[usize] = &[$($true_target, $false_target),*];
+            let mut i = 0;
+            while i < ids.len() {
+                assert!(ids[i] == i, "monkeys have to be numbered 0, 1, 2, ... in order");
+                assert!(divisors[i] != 0, "nothing is divisible by 0");
+                i += 1;
+            }
+            let mut i = 0;
+            while i < targets.len() {
+                assert!(targets[i] < ids.len(), "a monkey throws to a monkey that doesn't exist");
+                i += 1;
+            }
+        }
+        $crate::Djungle::builder()
+            $(.monkey(|m| $crate::monkeys!(@operation m.items([$($item),*]), $op $right)
+                .divisible_by($divisor)
+                .throw($true_target, $false_target)))*
+            .build()
+    }};
+    (@operation $m:expr, * old) => { $m.square() };
+    (@operation $m:expr, + old) => { $m.double() };
+    (@operation $m:expr, * $c:literal) => { $m.mul($c) };
+    (@operation $m:expr, + $c:literal) => { $m.add($c) };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{to_input, Djungle, Monkey, Parsable};
+
+    #[test]
+    fn test_same_as_parsed() {
+        let djungle: Djungle = monkeys! {
+            0: [79, 98] old * 19, /23 ? 2 : 3
+            1: [54, 65, 75, 74] old + 6, /19 ? 2 : 0
+            2: [79, 60, 97] old * old, /13 ? 1 : 3
+            3: [74] old + 3, /17 ? 0 : 1
+        }
+        .unwrap();
+        let parsed = Vec::<Monkey>::parse(include_str!("../test.txt").to_string());
+        assert_eq!(to_input(&djungle.monkeys), to_input(&parsed));
+    }
+
+    #[test]
+    fn test_other_shapes() {
+        // no items, doubling, trailing commas and semicolons
+        let mut djungle: Djungle<u64> = monkeys! {
+            0: [] old + old, /2 ? 1 : 0;
+            1: [3, 4,] old * 1, /5 ? 1 : 0;
+        }
+        .unwrap();
+        assert!(djungle.monkeys[0].items.is_empty());
+        djungle.round();
+        // 3 and 4 go to monkey 0, which doubles them and throws both back
+        assert_eq!(djungle.monkeys[1].inspections, 2);
+        djungle.round();
+        assert_eq!(djungle.monkeys[0].inspections, 2);
+    }
+
+    #[test]
+    fn test_too_big_for_worry_type() {
+        let djungle: Result<Djungle<u32>, String> = monkeys! {
+            0: [1] old + 1, /100000 ? 1 : 1
+            1: [1] old + 1, /100000 ? 0 : 0
+        };
+        assert_eq!(djungle.unwrap_err(), "mod factor does not fit into a u32");
+    }
+}
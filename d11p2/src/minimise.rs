@@ -0,0 +1,349 @@
+// Shrinking a jungle that makes something go wrong
+//
+// Delta debugging: `minimise` gets monkeys, a number of rounds and a predicate that says whether
+// it still goes wrong, and takes things away for as long as it does. In turns it
+// * removes monkeys, then items, in chunks that get smaller when no chunk can go (the "remove a
+//   complement" half of ddmin),
+// * plays fewer rounds (after the removing, fewer rounds first would mean more items are needed),
+// * makes constants smaller: worry levels, operation constants and divisors,
+// until a whole turn doesn't find anything. Rounds and constants are bisected, which assumes that a
+// smaller value that still goes wrong isn't hiding between two that don't. That's not always true,
+// but whatever comes out still goes wrong, it's just not the smallest possible.
+//
+// A removed monkey takes its items with it, whoever threw to it throws to itself instead.
+//
+// The predicate sees every candidate, so it should look for the failure it's after: a candidate
+// that goes wrong some other way (a panic in a different place, say) is kept just the same.
+
+use itertools::Itertools;
+
+use crate::{Item, Monkey, NumT, OperationParameter, Test};
+
+#[derive(Debug, Clone)]
+pub struct Reproducer {
+    pub monkeys: Vec<Monkey>,
+    pub rounds: usize,
+    /// How many candidates the predicate looked at.
+    pub tries: usize,
+}
+
+impl Reproducer {
+    /// The monkeys in the format of the input.
+    pub fn input(&self) -> String {
+        to_input(&self.monkeys)
+    }
+}
+
+/// Writes monkeys the way the input does, `Vec::<Monkey>::parse` reads them back.
+pub fn to_input(monkeys: &[Monkey]) -> String {
+    monkeys.iter().map(|m| m.to_string()).join("\n")
+}
+
+/// Shrinks the monkeys and rounds for as long as `fails(monkeys, rounds)` stays true.
+pub fn minimise<F>(monkeys: Vec<Monkey>, rounds: usize, fails: F) -> Reproducer
+where
+    F: FnMut(&[Monkey], usize) -> bool,
+{
+    let mut minimiser = Minimiser {
+        fails,
+        tries: 0,
+        monkeys: keep(&monkeys),
+        rounds,
+    };
+    assert!(
+        minimiser.fails(&minimiser.monkeys.clone(), rounds),
+        "nothing goes wrong with the jungle we start with"
+    );
+    loop {
+        let mut changed = minimiser.fewer_monkeys();
+        changed |= minimiser.fewer_items();
+        changed |= minimiser.fewer_rounds();
+        changed |= minimiser.smaller_constants();
+        if !changed {
+            break;
+        }
+    }
+    Reproducer {
+        monkeys: minimiser.monkeys,
+        rounds: minimiser.rounds,
+        tries: minimiser.tries,
+    }
+}
+
+struct Minimiser<F> {
+    fails: F,
+    tries: usize,
+    // the smallest jungle that still goes wrong so far
+    monkeys: Vec<Monkey>,
+    rounds: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Constant {
+    Item { monkey: usize, item: usize },
+    Operation(usize),
+    Divisor(usize),
+}
+
+impl<F: FnMut(&[Monkey], usize) -> bool> Minimiser<F> {
+    fn fails(&mut self, monkeys: &[Monkey], rounds: usize) -> bool {
+        self.tries += 1;
+        (self.fails)(monkeys, rounds)
+    }
+
+    fn fewer_rounds(&mut self) -> bool {
+        let monkeys = self.monkeys.clone();
+        let rounds = bisect(0, self.rounds as NumT, |r| self.fails(&monkeys, r as usize));
+        let changed = rounds < self.rounds as NumT;
+        self.rounds = rounds as usize;
+        changed
+    }
+
+    fn fewer_monkeys(&mut self) -> bool {
+        let mut monkeys = self.monkeys.clone();
+        let rounds = self.rounds;
+        let changed = remove_chunks(&mut monkeys, |kept| self.fails(&keep(kept), rounds));
+        self.monkeys = keep(&monkeys);
+        changed
+    }
+
+    fn fewer_items(&mut self) -> bool {
+        let mut items = self
+            .monkeys
+            .iter()
+            .enumerate()
+            .flat_map(|(id, m)| m.items.iter().map(move |i| (id, i.worry_level)))
+            .collect_vec();
+        let (monkeys, rounds) = (self.monkeys.clone(), self.rounds);
+        let changed = remove_chunks(&mut items, |kept| {
+            self.fails(&with_items(&monkeys, kept), rounds)
+        });
+        self.monkeys = with_items(&monkeys, &items);
+        changed
+    }
+
+    fn smaller_constants(&mut self) -> bool {
+        let mut constants = Vec::new();
+        for (id, monkey) in self.monkeys.iter().enumerate() {
+            constants
+                .extend((0..monkey.items.len()).map(|item| Constant::Item { monkey: id, item }));
+            if let OperationParameter::Constant(_) = monkey.operation.right {
+                constants.push(Constant::Operation(id));
+            }
+            constants.push(Constant::Divisor(id));
+        }
+
+        let mut changed = false;
+        for constant in constants {
+            let value = get(&self.monkeys, constant);
+            // nothing is divisible by 0
+            let smallest = match constant {
+                Constant::Divisor(_) => 1,
+                _ => 0,
+            };
+            let (monkeys, rounds) = (self.monkeys.clone(), self.rounds);
+            let smaller = bisect(smallest, value, |v| {
+                self.fails(&set(&monkeys, constant, v), rounds)
+            });
+            if smaller < value {
+                self.monkeys = set(&monkeys, constant, smaller);
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+// the smallest value between `low` and `high` that fails, `high` is known to fail
+fn bisect(mut low: NumT, mut high: NumT, mut fails: impl FnMut(NumT) -> bool) -> NumT {
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if fails(middle) {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+    high
+}
+
+// removes chunks for as long as it still fails without them, with smaller chunks when none can go
+fn remove_chunks<T: Clone>(list: &mut Vec<T>, mut fails: impl FnMut(&[T]) -> bool) -> bool {
+    let mut changed = false;
+    let mut chunks = 2;
+    while !list.is_empty() {
+        let size = list.len().div_ceil(chunks);
+        let mut removed = false;
+        let mut start = 0;
+        while start < list.len() {
+            let mut candidate = list.clone();
+            candidate.drain(start..(start + size).min(list.len()));
+            if fails(&candidate) {
+                *list = candidate;
+                removed = true;
+            } else {
+                start += size;
+            }
+        }
+        changed |= removed;
+        if removed {
+            chunks = (chunks - 1).max(2);
+        } else if size == 1 {
+            break;
+        } else {
+            chunks = (chunks * 2).min(list.len());
+        }
+    }
+    changed
+}
+
+// numbers the monkeys that are left from 0 again, throws to a monkey that's gone go to the thrower
+fn keep(kept: &[Monkey]) -> Vec<Monkey> {
+    let position = |id| kept.iter().position(|m: &Monkey| m.id == id);
+    kept.iter()
+        .enumerate()
+        .map(|(id, m)| Monkey {
+            id,
+            items: m.items.clone(),
+            inspections: m.inspections,
+            operation: m.operation.clone(),
+            test: Test {
+                divisor: m.test.divisor,
+                true_target: position(m.test.true_target).unwrap_or(id),
+                false_target: position(m.test.false_target).unwrap_or(id),
+            },
+        })
+        .collect()
+}
+
+fn with_items(monkeys: &[Monkey], items: &[(usize, NumT)]) -> Vec<Monkey> {
+    let mut monkeys = monkeys.to_vec();
+    for monkey in monkeys.iter_mut() {
+        monkey.items.clear();
+    }
+    for (id, level) in items {
+        monkeys[*id].items.push(Item {
+            worry_level: *level,
+        });
+    }
+    monkeys
+}
+
+fn get(monkeys: &[Monkey], constant: Constant) -> NumT {
+    match constant {
+        Constant::Item { monkey, item } => monkeys[monkey].items[item].worry_level,
+        Constant::Operation(monkey) => match monkeys[monkey].operation.right {
+            OperationParameter::Constant(c) => c,
+            OperationParameter::Old => unreachable!("old isn't a constant"),
+        },
+        Constant::Divisor(monkey) => monkeys[monkey].test.divisor,
+    }
+}
+
+fn set(monkeys: &[Monkey], constant: Constant, value: NumT) -> Vec<Monkey> {
+    let mut monkeys = monkeys.to_vec();
+    match constant {
+        Constant::Item { monkey, item } => monkeys[monkey].items[item].worry_level = value,
+        Constant::Operation(monkey) => {
+            monkeys[monkey].operation.right = OperationParameter::Constant(value)
+        }
+        Constant::Divisor(monkey) => monkeys[monkey].test.divisor = value,
+    }
+    monkeys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Djungle, Parsable};
+
+    fn test_monkeys() -> Vec<Monkey> {
+        Vec::<Monkey>::parse(include_str!("../test.txt").to_string())
+    }
+
+    fn inspections_after(monkeys: &[Monkey], rounds: usize) -> Vec<NumT> {
+        let mut djungle: Djungle = Djungle::lift(monkeys.to_vec());
+        for _ in 0..rounds {
+            djungle.round();
+        }
+        djungle.monkeys.iter().map(|m| m.inspections).collect()
+    }
+
+    #[test]
+    fn test_input_round_trip() {
+        assert_eq!(to_input(&test_monkeys()), include_str!("../test.txt"));
+        let mut monkeys = test_monkeys();
+        monkeys[1].items.clear();
+        let parsed = Vec::<Monkey>::parse(to_input(&monkeys));
+        assert_eq!(to_input(&parsed), to_input(&monkeys));
+        assert!(parsed[1].items.is_empty());
+    }
+
+    #[test]
+    fn test_removed_monkeys_are_not_thrown_to() {
+        // monkey 0 throws to 2 and 3, monkey 3 throws to 0 and 1
+        let monkeys = test_monkeys();
+        let kept = keep(&[monkeys[0].clone(), monkeys[3].clone()]);
+        assert_eq!(kept.iter().map(|m| m.id).collect_vec(), vec![0, 1]);
+        assert_eq!(
+            (kept[0].test.true_target, kept[0].test.false_target),
+            (0, 1)
+        );
+        assert_eq!(
+            (kept[1].test.true_target, kept[1].test.false_target),
+            (0, 1)
+        );
+    }
+
+    #[test]
+    fn test_minimise() {
+        // someone inspected at least 10 items: one monkey, one item and ten rounds do that
+        let fails = |monkeys: &[Monkey], rounds| {
+            inspections_after(monkeys, rounds).iter().any(|i| *i >= 10)
+        };
+        let reproducer = minimise(test_monkeys(), 20, fails);
+        assert_eq!(reproducer.monkeys.len(), 1);
+        assert!(fails(&reproducer.monkeys, reproducer.rounds));
+        // whatever is left is needed
+        let items = reproducer.monkeys[0].items.len();
+        assert_eq!(items * reproducer.rounds, 10);
+        assert!(!fails(&reproducer.monkeys, reproducer.rounds - 1));
+        // and it's written so that it parses back into the same jungle
+        let parsed = Vec::<Monkey>::parse(reproducer.input());
+        assert_eq!(to_input(&parsed), reproducer.input());
+        assert_eq!(reproducer.monkeys[0].test.divisor, 1);
+        assert!(reproducer.monkeys[0]
+            .items
+            .iter()
+            .all(|i| i.worry_level == 0));
+    }
+
+    #[test]
+    fn test_minimise_overflow() {
+        // part 2 worry levels that don't fit a u32, the way the u32 backend breaks
+        let fails = |monkeys: &[Monkey], rounds| {
+            let mut djungle: Djungle = Djungle::lift(monkeys.to_vec());
+            (0..rounds).any(|_| {
+                djungle.round();
+                djungle
+                    .monkeys
+                    .iter()
+                    .flat_map(|m| m.items.iter())
+                    .any(|i| i.worry_level > u32::MAX as NumT)
+            })
+        };
+        // the example gets there in round 59
+        let start = test_monkeys();
+        let reproducer = minimise(start.clone(), 100, fails);
+        assert!(fails(&reproducer.monkeys, reproducer.rounds));
+        assert!(!fails(&reproducer.monkeys, reproducer.rounds - 1));
+        // all four divisors are needed for a mod factor that large, but one item is enough
+        assert_eq!(reproducer.monkeys.len(), start.len());
+        let items = reproducer
+            .monkeys
+            .iter()
+            .map(|m| m.items.len())
+            .sum::<usize>();
+        assert_eq!(items, 1);
+    }
+}
@@ -0,0 +1,332 @@
+// The monkeys as the input writes them down
+//
+// Parsing, and what we can tell about a jungle before playing it: its mod factor and how large an
+// operation can make a worry level. Playing is up to the `Worry` the jungle is lifted to, see
+// lib.rs.
+//
+// build.rs includes this file, horizon.rs and fixture.rs (and nothing else of the library) to parse
+// and size the generated simulator exactly like the library does. So none of them may use anything
+// but std, itertools and each other. This file is `crate::monkey` in both, and both import its
+// types at the root, so `crate::Monkey` works as well.
+
+use std::fmt;
+
+use itertools::Itertools;
+
+pub type NumT = u128;
+
+/// How worry levels are kept from growing forever.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Relief {
+    /// Part 1: after an inspection the worry level is divided by three (rounded down).
+    DivideByThree,
+    /// Part 2: no relief, worry levels are reduced modulo the mod factor instead.
+    #[default]
+    None,
+}
+
+pub trait Parsable: Sized {
+    /// Says what's wrong with the input instead of panicking.
+    fn try_parse(input: &str) -> Result<Self, String>;
+
+    fn parse(input: String) -> Self {
+        Self::try_parse(&input).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+// the last word of a line as a number, the way most lines of the input end
+fn last_number<T: std::str::FromStr>(line: &str) -> Result<T, String>
+where
+    T::Err: fmt::Display,
+{
+    let word = line.split_whitespace().last().unwrap_or("");
+    word.parse::<T>()
+        .map_err(|e| format!("{:?} in {:?}: {}", word, line, e))
+}
+
+#[derive(Debug, Clone)]
+pub struct Monkey<W = NumT> {
+    pub id: usize,
+    pub items: Vec<Item<W>>,
+    pub inspections: NumT,
+    pub test: Test,
+    pub operation: Operation,
+}
+
+// a monkey the way the input writes it down, see `minimise::to_input` for a whole jungle
+impl<W: fmt::Display> fmt::Display for Monkey<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Monkey {}:", self.id)?;
+        writeln!(
+            f,
+            "  Starting items: {}",
+            self.items.iter().map(|i| &i.worry_level).join(", ")
+        )?;
+        writeln!(f, "  Operation: {}", self.operation)?;
+        writeln!(f, "  Test: divisible by {}", self.test.divisor)?;
+        writeln!(f, "    If true: throw to monkey {}", self.test.true_target)?;
+        writeln!(
+            f,
+            "    If false: throw to monkey {}",
+            self.test.false_target
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Test {
+    pub divisor: NumT,
+    pub true_target: usize,
+    pub false_target: usize,
+}
+
+impl Parsable for Test {
+    fn try_parse(input: &str) -> Result<Self, String> {
+        // Tests have three lines
+        let [divisor, true_target, false_target] = input.lines().collect::<Vec<_>>()[..] else {
+            return Err(format!("Input for Test is fucked: {}", input));
+        };
+
+        // parse the first line as divisor
+        // Test: divisible by 23
+        // into 23
+        let divisor = last_number::<NumT>(divisor)?;
+        // the test couldn't tell anything apart, and would divide by zero
+        if divisor == 0 {
+            return Err(format!("Divisible by 0 in {:?}", input));
+        }
+
+        // parse the second line as true target
+        //     If true: throw to monkey 2
+        // into 2
+        let true_target = last_number::<usize>(true_target)?;
+
+        // parse the third line as false target
+        //     If false: throw to monkey 3
+        // into 3
+        let false_target = last_number::<usize>(false_target)?;
+
+        Ok(Test {
+            divisor,
+            true_target,
+            false_target,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Operation {
+    pub operation: OperationType,
+    pub right: OperationParameter,
+}
+
+#[derive(Debug, Clone)]
+pub enum OperationParameter {
+    Constant(NumT),
+    Old,
+}
+
+#[derive(Debug, Clone)]
+pub enum OperationType {
+    Add,
+    Multiply,
+}
+
+impl Operation {
+    /// The result for `old`, or `None` if it doesn't fit into a u128.
+    pub(crate) fn worst_case(&self, old: NumT) -> Option<NumT> {
+        match (&self.operation, &self.right) {
+            (OperationType::Add, OperationParameter::Constant(c)) => old.checked_add(*c),
+            (OperationType::Add, OperationParameter::Old) => old.checked_add(old),
+            (OperationType::Multiply, OperationParameter::Constant(c)) => old.checked_mul(*c),
+            (OperationType::Multiply, OperationParameter::Old) => old.checked_mul(old),
+        }
+    }
+}
+
+impl Parsable for Operation {
+    fn try_parse(input: &str) -> Result<Operation, String> {
+        let Some((_, right_unparsed)) = input.split_once(['*', '+']) else {
+            return Err(format!("Operation not found in {:?}", input));
+        };
+        // whatever is left of the right side is the operator
+        let operation = match input[..input.len() - right_unparsed.len()].chars().last() {
+            Some('*') => OperationType::Multiply,
+            _ => OperationType::Add,
+        };
+
+        let right = match right_unparsed.trim() {
+            "old" => OperationParameter::Old,
+            c => OperationParameter::Constant(
+                c.parse::<NumT>()
+                    .map_err(|e| format!("{:?} in {:?}: {}", c, input, e))?,
+            ),
+        };
+        Ok(Operation { operation, right })
+    }
+}
+
+// the same way it's written in the input, so it parses back into the same operation
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operator = match self.operation {
+            OperationType::Add => '+',
+            OperationType::Multiply => '*',
+        };
+        match self.right {
+            OperationParameter::Constant(c) => write!(f, "new = old {} {}", operator, c),
+            OperationParameter::Old => write!(f, "new = old {} old", operator),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item<W = NumT> {
+    pub worry_level: W,
+}
+
+// The monkeys exactly as they are written down, with unreduced worry levels. This doesn't depend on
+// how the worry levels will be stored later on, so it's what we look at before picking a `Worry`.
+impl Parsable for Vec<Monkey> {
+    fn try_parse(input: &str) -> Result<Self, String> {
+        let mut monkeys = Vec::new();
+        let lines = input.trim_end().lines().collect::<Vec<_>>();
+        // six lines per monkey, and an empty one in between
+        for (monkey_id, lines) in lines.chunks(7).enumerate() {
+            if lines.len() < 6 {
+                return Err(format!(
+                    "Monkey {} ends after {} lines",
+                    monkey_id,
+                    lines.len()
+                ));
+            }
+            if let Some(extra) = lines.get(6).filter(|l| !l.trim().is_empty()) {
+                return Err(format!(
+                    "Monkey {} has an extra line {:?}",
+                    monkey_id, extra
+                ));
+            }
+            // the first line only has the id
+            let (items, operation, test) = (lines[1], lines[2], lines[3..6].join("\n"));
+
+            // parse items
+            //   Starting items: 79, 98
+            // into: [79, 98]
+            let Some((_, items)) = items.split_once(':') else {
+                return Err(format!("No items for monkey {} in {:?}", monkey_id, items));
+            };
+            let items = items
+                .split(',')
+                // a monkey might start without items
+                .filter(|i| !i.trim().is_empty())
+                .map(|i| {
+                    let worry_level = i
+                        .trim()
+                        .parse::<NumT>()
+                        .map_err(|e| format!("{:?} in {:?}: {}", i, items, e))?;
+                    Ok(Item { worry_level })
+                })
+                .collect::<Result<_, String>>()?;
+            // parse operation
+            let operation = Operation::try_parse(operation)?;
+            // parse next three lines as test
+            let test = Test::try_parse(&test)?;
+
+            monkeys.push(Monkey {
+                id: monkey_id,
+                items,
+                inspections: 0,
+                operation,
+                test,
+            });
+        }
+
+        check_targets(&monkeys)?;
+        Ok(monkeys)
+    }
+}
+
+/// Every throw has to land somewhere, whoever put the monkeys together.
+pub(crate) fn check_targets(monkeys: &[Monkey]) -> Result<(), String> {
+    for monkey in monkeys.iter() {
+        for target in [monkey.test.true_target, monkey.test.false_target] {
+            if target >= monkeys.len() {
+                return Err(format!(
+                    "Monkey {} throws to monkey {}, there are only {}",
+                    monkey.id,
+                    target,
+                    monkeys.len()
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The product of all divisors, or `None` if it doesn't fit into a u128.
+pub fn mod_factor<W>(monkeys: &[Monkey<W>]) -> Option<NumT> {
+    monkeys
+        .iter()
+        .try_fold(1 as NumT, |acc, m| acc.checked_mul(m.test.divisor))
+}
+
+/// The largest worry level the jungle can ever produce, or `None` if it doesn't fit into a u128.
+pub fn worst_case(monkeys: &[Monkey]) -> Option<NumT> {
+    let mod_factor = mod_factor(monkeys)?;
+    let largest_start = monkeys
+        .iter()
+        .flat_map(|m| m.items.iter())
+        .map(|i| i.worry_level)
+        .max()
+        .unwrap_or(0);
+    monkeys
+        .iter()
+        .map(|m| m.operation.worst_case(mod_factor.saturating_sub(1)))
+        .try_fold(mod_factor.max(largest_start), |acc, w| Some(acc.max(w?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operation_display_parses_back() {
+        for input in [
+            "new = old * 19",
+            "new = old + 6",
+            "new = old * old",
+            "new = old + old",
+        ] {
+            let operation = Operation::parse(input.to_string());
+            assert_eq!(operation.to_string(), input);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let input = include_str!("../test.txt");
+        assert_eq!(Vec::<Monkey>::try_parse(input).unwrap().len(), 4);
+        // trailing empty lines don't make another monkey
+        assert_eq!(
+            Vec::<Monkey>::try_parse(&format!("{}\n\n", input))
+                .unwrap()
+                .len(),
+            4
+        );
+        for broken in [
+            input.replace("old * 19", "old / 19"),
+            input.replace("old * 19", "old * ü19"),
+            input.replace("79, 98", "79, -98"),
+            input.replace("divisible by 23", "divisible by 0"),
+            input.replace("throw to monkey 3", "throw to monkey 4"),
+            input.replace("Starting items:", "Starting items"),
+            input[..input.len() - 30].to_string(),
+            format!("{}\nMonkey 4:", input),
+        ] {
+            assert!(Vec::<Monkey>::try_parse(&broken).is_err(), "{}", broken);
+        }
+        // the operator isn't always a single byte away from the start
+        let operation = Operation::try_parse("ü = ö * 3").unwrap();
+        assert_eq!(operation.to_string(), "new = old * 3");
+    }
+}
@@ -0,0 +1,168 @@
+// Items as a multiset
+//
+// Two items with the same worry level sitting at the same monkey will do exactly the same thing
+// from now on, so there's no need to simulate both. Every monkey holds (worry level, count) pairs
+// instead, items with the same worry level are merged when they're caught, and a monkey inspecting
+// a pair counts `count` inspections. The order of the queue is lost, but the monkey business
+// doesn't care about it.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{Djungle, NumT, Operation, Relief, Test, Worry};
+
+pub struct Multiset<W: Worry + Eq + Hash = NumT> {
+    /// What every monkey holds, as (worry level, number of items) pairs.
+    pub items: Vec<Vec<(W, NumT)>>,
+    pub inspections: Vec<NumT>,
+    // where a worry level is in `items`, to merge items when they're caught
+    positions: Vec<HashMap<W, usize>>,
+    operations: Vec<Operation>,
+    tests: Vec<Test>,
+    mod_factor: W::Modulus,
+    relief: Relief,
+}
+
+impl<W: Worry + Eq + Hash> From<Djungle<W>> for Multiset<W> {
+    fn from(djungle: Djungle<W>) -> Self {
+        let monkey_count = djungle.monkeys.len();
+        let mut multiset = Multiset {
+            items: vec![Vec::new(); monkey_count],
+            inspections: Vec::new(),
+            positions: vec![HashMap::new(); monkey_count],
+            operations: Vec::new(),
+            tests: Vec::new(),
+            mod_factor: djungle.mod_factor,
+            relief: djungle.relief,
+        };
+        for (id, monkey) in djungle.monkeys.into_iter().enumerate() {
+            for item in monkey.items {
+                multiset.catch(id, item.worry_level, 1);
+            }
+            multiset.inspections.push(monkey.inspections);
+            multiset.operations.push(monkey.operation);
+            multiset.tests.push(monkey.test);
+        }
+        multiset
+    }
+}
+
+impl<W: Worry + Eq + Hash> Multiset<W> {
+    fn catch(&mut self, monkey: usize, worry: W, count: NumT) {
+        match self.positions[monkey].get(&worry) {
+            Some(position) => self.items[monkey][*position].1 += count,
+            None => {
+                self.positions[monkey].insert(worry.clone(), self.items[monkey].len());
+                self.items[monkey].push((worry, count));
+            }
+        }
+    }
+
+    pub fn round(&mut self) {
+        for monkey in 0..self.operations.len() {
+            let items = std::mem::take(&mut self.items[monkey]);
+            self.positions[monkey].clear();
+            for (mut worry, count) in items {
+                self.inspections[monkey] += count;
+                match self.relief {
+                    Relief::DivideByThree => {
+                        worry = self.operations[monkey].evaluate(&worry, &self.mod_factor);
+                        worry.divide_by_three();
+                    }
+                    Relief::None => {
+                        worry.reduce(&self.mod_factor);
+                        worry = self.operations[monkey].evaluate(&worry, &self.mod_factor);
+                    }
+                }
+                let test = &self.tests[monkey];
+                let target = if worry.is_divisible_by(test.divisor, &self.mod_factor) {
+                    test.true_target
+                } else {
+                    test.false_target
+                };
+                self.catch(target, worry, count);
+            }
+        }
+    }
+
+    /// How many items every monkey holds, counting duplicates.
+    pub fn item_counts(&self) -> Vec<NumT> {
+        self.items
+            .iter()
+            .map(|items| items.iter().map(|(_, count)| count).sum())
+            .collect()
+    }
+
+    pub fn monkey_business(&self) -> NumT {
+        let mut inspections = self.inspections.clone();
+        inspections.sort_unstable();
+        inspections.iter().rev().take(2).product()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_same_as_plain;
+    use crate::{Item, Monkey, Parsable, Residues};
+
+    #[test]
+    fn test_same_as_djungle() {
+        for relief in [Relief::DivideByThree, Relief::None] {
+            assert_same_as_plain::<u64, _>(
+                relief,
+                20,
+                Multiset::from,
+                Multiset::round,
+                |djungle, multiset| {
+                    let counts = djungle
+                        .monkeys
+                        .iter()
+                        .map(|m| m.items.len() as NumT)
+                        .collect::<Vec<NumT>>();
+                    assert_eq!(multiset.item_counts(), counts);
+                    assert_eq!(multiset.monkey_business(), djungle.monkey_business());
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn test_identical_items_are_merged() {
+        let mut monkeys = Vec::<Monkey>::parse(include_str!("../test.txt").to_string());
+        for monkey in monkeys.iter_mut() {
+            monkey.items = monkey
+                .items
+                .iter()
+                .flat_map(|i| std::iter::repeat_n(i.clone(), 10000))
+                .collect();
+        }
+        let mut multiset = Multiset::from(Djungle::<Residues>::lift(monkeys));
+        assert_eq!(multiset.items.iter().map(|i| i.len()).sum::<usize>(), 10);
+        for _ in 0..10000 {
+            multiset.round();
+        }
+        // every item is there 10000 times, so every monkey inspects 10000 times as much
+        assert_eq!(multiset.monkey_business(), 2713310158 * 10000 * 10000);
+        assert!(multiset.items.iter().map(|i| i.len()).sum::<usize>() <= 10);
+    }
+
+    #[test]
+    fn test_catch_merges_into_existing_pair() {
+        let mut multiset = Multiset::from(Djungle::<u64>::new(vec![Monkey {
+            id: 0,
+            items: vec![Item { worry_level: 5 }, Item { worry_level: 5 }],
+            inspections: 0,
+            operation: Operation::parse("new = old + 0".to_string()),
+            test: Test {
+                divisor: 5,
+                true_target: 0,
+                false_target: 0,
+            },
+        }]));
+        assert_eq!(multiset.items[0], vec![(5, 2)]);
+        multiset.catch(0, 5, 3);
+        multiset.catch(0, 7, 1);
+        assert_eq!(multiset.items[0], vec![(5, 5), (7, 1)]);
+    }
+}
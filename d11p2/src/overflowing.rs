@@ -0,0 +1,178 @@
+// Worry levels that notice when they overflow
+//
+// A plain integer panics when an operation doesn't fit (or wraps around in release builds, which is
+// worse). `Overflowing` does what the `overflowing_*` methods of the integers do: it wraps around
+// and remembers that it did. The flag sticks to the item from then on, so after a round we can ask
+// the jungle whether anything went wrong instead of deciding up front whether it could.
+//
+// Everything else works like the plain integer, the mod factor is the modulus.
+
+use std::fmt;
+
+use crate::{Djungle, NumT, Worry};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflowing<T> {
+    /// The worry level, wrapped around if it overflowed.
+    pub level: T,
+    /// Whether this level (or any level it was computed from) ever overflowed.
+    pub overflowed: bool,
+}
+
+impl<T: fmt::Display> fmt::Display for Overflowing<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.level.fmt(f)
+    }
+}
+
+impl<T> Overflowing<T> {
+    // what `overflowing_add` and friends return
+    fn new((level, overflowed): (T, bool)) -> Self {
+        Overflowing { level, overflowed }
+    }
+
+    fn and(self, overflowed: bool) -> Self {
+        Overflowing {
+            overflowed: self.overflowed || overflowed,
+            ..self
+        }
+    }
+}
+
+impl<T> Djungle<Overflowing<T>>
+where
+    Overflowing<T>: Worry,
+{
+    /// Whether any item the monkeys hold overflowed on the way.
+    pub fn overflowed(&self) -> bool {
+        self.monkeys
+            .iter()
+            .flat_map(|m| m.items.iter())
+            .any(|i| i.worry_level.overflowed)
+    }
+}
+
+macro_rules! impl_worry_for_overflowing {
+    ($($t:ty),*) => {$(
+        impl Overflowing<$t> {
+            // a constant that doesn't fit overflows as well
+            fn constant(c: NumT) -> ($t, bool) {
+                (c as $t, <$t>::try_from(c).is_err())
+            }
+        }
+
+        impl Worry for Overflowing<$t> {
+            type Modulus = $t;
+
+            fn modulus(divisors: &[NumT]) -> Result<$t, String> {
+                <$t as Worry>::modulus(divisors)
+            }
+
+            // a level that doesn't fit has already overflowed
+            fn try_lift(level: NumT, _: &$t) -> Result<Self, String> {
+                Ok(Overflowing::new(Self::constant(level)))
+            }
+
+            fn reduce(&mut self, modulus: &$t) {
+                self.level %= modulus;
+            }
+
+            fn divide_by_three(&mut self) {
+                self.level /= 3;
+            }
+
+            fn add(&self, right: &Self, _: &$t) -> Self {
+                Overflowing::new(self.level.overflowing_add(right.level))
+                    .and(self.overflowed || right.overflowed)
+            }
+
+            fn mul(&self, right: &Self, _: &$t) -> Self {
+                Overflowing::new(self.level.overflowing_mul(right.level))
+                    .and(self.overflowed || right.overflowed)
+            }
+
+            fn add_constant(&self, right: NumT, _: &$t) -> Self {
+                let (right, too_big) = Self::constant(right);
+                Overflowing::new(self.level.overflowing_add(right)).and(self.overflowed || too_big)
+            }
+
+            fn mul_constant(&self, right: NumT, _: &$t) -> Self {
+                let (right, too_big) = Self::constant(right);
+                Overflowing::new(self.level.overflowing_mul(right)).and(self.overflowed || too_big)
+            }
+
+            fn is_divisible_by(&self, divisor: NumT, _: &$t) -> bool {
+                // a level is smaller than any divisor that doesn't fit
+                match <$t>::try_from(divisor) {
+                    Ok(divisor) => self.level.is_multiple_of(divisor),
+                    Err(_) => self.level == 0,
+                }
+            }
+        }
+
+        // for throw logs, which write levels down as `NumT`
+        impl From<Overflowing<$t>> for NumT {
+            fn from(worry: Overflowing<$t>) -> NumT {
+                worry.level as NumT
+            }
+        }
+    )*};
+}
+
+impl_worry_for_overflowing!(u32, u64, u128);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Parsable, Relief};
+
+    #[test]
+    fn test_same_as_plain_until_it_overflows() {
+        let input = include_str!("../test.txt").to_string();
+        let mut plain: Djungle<u64> = Djungle::parse(input.clone());
+        let mut checked: Djungle<Overflowing<u32>> = Djungle::parse(input);
+        // reduced levels stay below 96577, but squaring them doesn't fit into a u32
+        loop {
+            let (Some(expected), Some(actual)) = (plain.step(), checked.step()) else {
+                continue;
+            };
+            assert_eq!(actual.monkey, expected.monkey);
+            if actual.after.overflowed {
+                assert!(expected.after > u32::MAX as u64);
+                break;
+            }
+            assert_eq!(actual.after.level as u64, expected.after);
+            assert_eq!(actual.target, expected.target);
+        }
+        assert!(checked.overflowed());
+    }
+
+    #[test]
+    fn test_part1_overflows_a_u128() {
+        // squaring without the mod factor gets out of hand quickly, even divided by three
+        let mut dj: Djungle<Overflowing<u128>> =
+            Djungle::parse(include_str!("../test.txt").to_string())
+                .with_relief(Relief::DivideByThree);
+        for _ in 0..20 {
+            dj.round();
+        }
+        assert!(!dj.overflowed());
+        for _ in 20..200 {
+            dj.round();
+        }
+        assert!(dj.overflowed());
+    }
+
+    #[test]
+    fn test_constants() {
+        let big = Overflowing::<u32>::lift(1 << 40, &7);
+        assert!(big.overflowed);
+        let small = Overflowing::<u32>::lift(3, &7);
+        assert!(!small.overflowed);
+        assert!(small.mul_constant(1 << 40, &7).overflowed);
+        assert!(small.add(&big, &7).overflowed);
+        assert_eq!(small.mul_constant(5, &7), Overflowing::new((15, false)));
+        assert!(!small.is_divisible_by(1 << 40, &7));
+        assert_eq!(NumT::from(small), 3);
+    }
+}
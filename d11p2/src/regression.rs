@@ -0,0 +1,293 @@
+// Regression tests from fixture directories
+//
+// Every directory in `fixtures/` has an input and what's expected of it, `part1.txt` (with relief)
+// and/or `part2.txt` (without). The input is either an `input.txt` in the directory, or an `input`
+// file with the path of one relative to the directory, so the puzzle inputs we already have don't
+// need another copy. The expectations look like this:
+//
+//     # comments and empty lines are fine
+//     rounds: 20
+//     items after 1: 20, 23, 27, 26 | 2080, 25, 167, 207, 401, 1046 ||
+//     inspections after 20: 101, 95, 7, 105
+//     monkey business: 10605
+//
+// Items are listed per monkey, separated by `|`, after whichever rounds are worth looking at. In
+// part 2 they're compared modulo the mod factor, which is all that's left of them anyway.
+//
+// build.rs writes one test per fixture directory, so adding a directory adds a test. `d11expect`
+// writes the expectations of an input as the current code sees them.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use itertools::Itertools;
+
+use crate::{Djungle, Monkey, NumT, Parsable, Relief};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expected {
+    pub relief: Relief,
+    pub rounds: usize,
+    /// Every monkey's items after the round.
+    pub items: BTreeMap<usize, Vec<Vec<NumT>>>,
+    /// Every monkey's inspections after the round.
+    pub inspections: BTreeMap<usize, Vec<NumT>>,
+    pub monkey_business: Option<NumT>,
+}
+
+impl Expected {
+    pub fn parse(text: &str, relief: Relief) -> Result<Self, String> {
+        let numbers = |list: &str| {
+            list.split(',')
+                .map(|n| n.trim())
+                .filter(|n| !n.is_empty())
+                .map(|n| n.parse::<NumT>().map_err(|e| format!("{:?}: {}", n, e)))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let round = |round: &str| {
+            round
+                .trim()
+                .parse::<usize>()
+                .map_err(|e| format!("round {:?}: {}", round, e))
+        };
+
+        let mut expected = Expected {
+            relief,
+            rounds: 0,
+            items: BTreeMap::new(),
+            inspections: BTreeMap::new(),
+            monkey_business: None,
+        };
+        for line in text.lines().map(|l| l.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                return Err(format!("no ':' in {:?}", line));
+            };
+            let words = key.split_whitespace().collect::<Vec<_>>();
+            match words.as_slice() {
+                ["rounds"] => expected.rounds = round(value)?,
+                ["items", "after", r] => {
+                    let items = value.split('|').map(numbers).collect::<Result<_, _>>()?;
+                    expected.items.insert(round(r)?, items);
+                }
+                ["inspections", "after", r] => {
+                    expected.inspections.insert(round(r)?, numbers(value)?);
+                }
+                ["monkey", "business"] => {
+                    expected.monkey_business = Some(
+                        numbers(value)?
+                            .first()
+                            .copied()
+                            .ok_or("monkey business without a number".to_string())?,
+                    );
+                }
+                _ => return Err(format!("unknown expectation {:?}", line)),
+            }
+        }
+
+        let last = expected
+            .items
+            .keys()
+            .chain(expected.inspections.keys())
+            .max();
+        if let Some(last) = last.filter(|r| **r > expected.rounds) {
+            return Err(format!(
+                "expectations after round {}, but only {} rounds are played",
+                last, expected.rounds
+            ));
+        }
+        Ok(expected)
+    }
+
+    /// What the jungle does now, with items and inspections after the given rounds.
+    pub fn record(monkeys: &[Monkey], relief: Relief, rounds: usize, after: &[usize]) -> Self {
+        let mut djungle: Djungle = Djungle::lift(monkeys.to_vec()).with_relief(relief);
+        let mut expected = Expected {
+            relief,
+            rounds,
+            items: BTreeMap::new(),
+            inspections: BTreeMap::new(),
+            monkey_business: None,
+        };
+        for round in 1..=rounds {
+            djungle.round();
+            if after.contains(&round) {
+                expected.items.insert(round, items(&djungle));
+                expected.inspections.insert(round, inspections(&djungle));
+            }
+        }
+        expected.monkey_business = Some(djungle.monkey_business());
+        expected
+    }
+
+    /// Plays the rounds and says where the jungle first does something else.
+    pub fn check(&self, monkeys: &[Monkey]) -> Result<(), String> {
+        let mut djungle: Djungle = Djungle::lift(monkeys.to_vec()).with_relief(self.relief);
+        let mod_factor = djungle.mod_factor;
+        let reduce = |level: NumT| match self.relief {
+            Relief::DivideByThree => level,
+            Relief::None => level % mod_factor,
+        };
+        for round in 1..=self.rounds {
+            djungle.round();
+            if let Some(expected) = self.items.get(&round) {
+                let actual = items(&djungle);
+                let same = expected.len() == actual.len()
+                    && expected.iter().zip(actual.iter()).all(|(e, a)| {
+                        e.len() == a.len() && e.iter().zip(a).all(|(e, a)| reduce(*e) == reduce(*a))
+                    });
+                if !same {
+                    return Err(format!(
+                        "items after round {}: expected {}, got {}",
+                        round,
+                        show_items(expected),
+                        show_items(&actual)
+                    ));
+                }
+            }
+            if let Some(expected) = self.inspections.get(&round) {
+                let actual = inspections(&djungle);
+                if *expected != actual {
+                    return Err(format!(
+                        "inspections after round {}: expected {:?}, got {:?}",
+                        round, expected, actual
+                    ));
+                }
+            }
+        }
+        match self.monkey_business {
+            Some(expected) if expected != djungle.monkey_business() => Err(format!(
+                "monkey business after {} rounds: expected {}, got {}",
+                self.rounds,
+                expected,
+                djungle.monkey_business()
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+// the file format `parse` reads
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "rounds: {}", self.rounds)?;
+        for round in 1..=self.rounds {
+            if let Some(items) = self.items.get(&round) {
+                writeln!(f, "items after {}: {}", round, show_items(items))?;
+            }
+            if let Some(inspections) = self.inspections.get(&round) {
+                writeln!(
+                    f,
+                    "inspections after {}: {}",
+                    round,
+                    inspections.iter().join(", ")
+                )?;
+            }
+        }
+        if let Some(monkey_business) = self.monkey_business {
+            writeln!(f, "monkey business: {}", monkey_business)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks both parts of a fixture directory, whichever of them it has.
+pub fn check_fixture(dir: &Path) -> Result<(), String> {
+    let read = |name: &str| std::fs::read_to_string(dir.join(name));
+    let input = match read("input") {
+        Ok(path) => dir.join(path.trim()),
+        Err(_) => dir.join("input.txt"),
+    };
+    let input =
+        std::fs::read_to_string(&input).map_err(|e| format!("{}: {}", input.display(), e))?;
+    let monkeys = Vec::<Monkey>::try_parse(&input)?;
+    let mut checked = 0;
+    for (file, relief) in [
+        ("part1.txt", Relief::DivideByThree),
+        ("part2.txt", Relief::None),
+    ] {
+        let Ok(text) = read(file) else {
+            continue;
+        };
+        Expected::parse(&text, relief)
+            .and_then(|expected| expected.check(&monkeys))
+            .map_err(|e| format!("{}: {}", dir.join(file).display(), e))?;
+        checked += 1;
+    }
+    if checked == 0 {
+        return Err(format!("{} expects nothing", dir.display()));
+    }
+    Ok(())
+}
+
+fn items(djungle: &Djungle) -> Vec<Vec<NumT>> {
+    djungle
+        .monkeys
+        .iter()
+        .map(|m| m.items.iter().map(|i| i.worry_level).collect())
+        .collect()
+}
+
+fn inspections(djungle: &Djungle) -> Vec<NumT> {
+    djungle.monkeys.iter().map(|m| m.inspections).collect()
+}
+
+// monkeys without items are just `||`
+fn show_items(items: &[Vec<NumT>]) -> String {
+    items
+        .iter()
+        .map(|m| match m.is_empty() {
+            true => String::new(),
+            false => format!(" {} ", m.iter().join(", ")),
+        })
+        .join("|")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // one test per directory in fixtures/, see build.rs
+    include!(concat!(env!("OUT_DIR"), "/fixture_tests.rs"));
+
+    fn test_monkeys() -> Vec<Monkey> {
+        Vec::<Monkey>::parse(include_str!("../test.txt").to_string())
+    }
+
+    #[test]
+    fn test_record_and_parse() {
+        let recorded = Expected::record(&test_monkeys(), Relief::None, 1000, &[1, 20, 1000]);
+        let parsed = Expected::parse(&recorded.to_string(), Relief::None).unwrap();
+        assert_eq!(parsed, recorded);
+        assert_eq!(parsed.inspections[&20], vec![99, 97, 8, 103]);
+        assert_eq!(parsed.check(&test_monkeys()), Ok(()));
+    }
+
+    #[test]
+    fn test_wrong_expectations() {
+        let mut expected = Expected::parse(
+            "rounds: 20\ninspections after 20: 101, 95, 7, 105\nmonkey business: 10605",
+            Relief::DivideByThree,
+        )
+        .unwrap();
+        assert_eq!(expected.check(&test_monkeys()), Ok(()));
+        expected.monkey_business = Some(10604);
+        assert!(expected
+            .check(&test_monkeys())
+            .unwrap_err()
+            .contains("10605"));
+        expected
+            .items
+            .insert(1, vec![vec![20], vec![], vec![], vec![]]);
+        let error = expected.check(&test_monkeys()).unwrap_err();
+        assert!(error.starts_with("items after round 1"), "{}", error);
+
+        assert!(Expected::parse("rounds: 2\nitems after 3: 1 | 2", Relief::None).is_err());
+        assert!(Expected::parse("monkey mood: 3", Relief::None).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+// Residue vector worry levels
+//
+// Instead of one big number reduced by the mod factor, an item only remembers its worry level
+// modulo every distinct divisor (Chinese Remainder style). That's all the tests ever look at, and
+// adding or multiplying works on every residue on its own, so:
+// * no wide multiplications: a residue is below its divisor, so a product of two fits into a u64
+// * no overflow, no matter how many monkeys there are, as long as each divisor fits into a u32
+
+use std::rc::Rc;
+
+use crate::{NumT, Worry};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Residues {
+    // one residue per entry of the modulus, in the same order
+    residues: Vec<u32>,
+}
+
+impl Residues {
+    pub fn residues(&self) -> &[u32] {
+        &self.residues
+    }
+
+    // applies `f(index, residue, divisor)` to every residue and reduces the result again
+    fn map(&self, divisors: &[u32], f: impl Fn(usize, u64, u64) -> u64) -> Self {
+        Residues {
+            residues: self
+                .residues
+                .iter()
+                .zip(divisors.iter())
+                .enumerate()
+                .map(|(i, (r, d))| (f(i, *r as u64, *d as u64) % *d as u64) as u32)
+                .collect(),
+        }
+    }
+}
+
+impl Worry for Residues {
+    // the distinct divisors, shared by all items
+    type Modulus = Rc<[u32]>;
+
+    fn modulus(divisors: &[NumT]) -> Result<Rc<[u32]>, String> {
+        let mut distinct = divisors
+            .iter()
+            .map(|d| {
+                u32::try_from(*d).map_err(|_| format!("divisor {} does not fit into a u32", d))
+            })
+            .collect::<Result<Vec<u32>, _>>()?;
+        distinct.sort_unstable();
+        distinct.dedup();
+        Ok(distinct.into())
+    }
+
+    fn try_lift(level: NumT, modulus: &Rc<[u32]>) -> Result<Self, String> {
+        Ok(Residues {
+            residues: modulus
+                .iter()
+                .map(|d| (level % *d as NumT) as u32)
+                .collect(),
+        })
+    }
+
+    fn reduce(&mut self, _: &Rc<[u32]>) {
+        // residues are always reduced
+    }
+
+    fn divide_by_three(&mut self) {
+        unreachable!("residue vectors can't be divided by three")
+    }
+
+    fn can_divide_by_three() -> Result<(), String> {
+        Err("residue vectors can't be divided by three".to_string())
+    }
+
+    fn add(&self, right: &Self, modulus: &Rc<[u32]>) -> Self {
+        self.map(modulus, |i, r, _| r + right.residues[i] as u64)
+    }
+
+    fn mul(&self, right: &Self, modulus: &Rc<[u32]>) -> Self {
+        self.map(modulus, |i, r, _| r * right.residues[i] as u64)
+    }
+
+    fn add_constant(&self, right: NumT, modulus: &Rc<[u32]>) -> Self {
+        self.map(modulus, |_, r, d| r + (right % d as NumT) as u64)
+    }
+
+    fn mul_constant(&self, right: NumT, modulus: &Rc<[u32]>) -> Self {
+        self.map(modulus, |_, r, d| r * (right % d as NumT) as u64)
+    }
+
+    fn is_divisible_by(&self, divisor: NumT, modulus: &Rc<[u32]>) -> bool {
+        let position = modulus
+            .iter()
+            .position(|d| *d as NumT == divisor)
+            .expect("divisor is not part of the modulus");
+        self.residues[position] == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Djungle, Parsable};
+
+    fn test_input() -> String {
+        include_str!("../test.txt").to_string()
+    }
+
+    #[test]
+    fn test_lift_keeps_one_residue_per_distinct_divisor() {
+        let modulus = Residues::modulus(&[23, 19, 13, 17, 19]).unwrap();
+        assert_eq!(&*modulus, &[13, 17, 19, 23]);
+        let r = Residues::lift(79, &modulus);
+        assert_eq!(r.residues(), &[1, 11, 3, 10]);
+    }
+
+    #[test]
+    fn test_same_throws_as_plain_worry_levels() {
+        let mut plain: Djungle = Djungle::parse(test_input());
+        let mut residues: Djungle<Residues> = Djungle::parse(test_input());
+        for _ in 0..20 {
+            plain.round();
+            residues.round();
+            for (p, r) in plain.monkeys.iter().zip(residues.monkeys.iter()) {
+                let lowered = p
+                    .items
+                    .iter()
+                    .map(|i| Residues::lift(i.worry_level, &residues.mod_factor))
+                    .collect::<Vec<Residues>>();
+                let actual = r
+                    .items
+                    .iter()
+                    .map(|i| i.worry_level.clone())
+                    .collect::<Vec<Residues>>();
+                assert_eq!(actual, lowered);
+            }
+        }
+    }
+
+    #[test]
+    fn test_monkey_business() {
+        let mut dj: Djungle<Residues> = Djungle::parse(test_input());
+        for _ in 0..10000 {
+            dj.round();
+        }
+        assert_eq!(dj.monkey_business(), 2713310158)
+    }
+}
@@ -0,0 +1,266 @@
+// Driving a jungle lazily
+//
+// Instead of calling `round()` in a loop, a jungle can be consumed as an iterator, either round by
+// round (`rounds()`) or inspection by inspection (`events()`). Both go on forever, use `take` to
+// stop. The fine grained one is built on `step()`, which lets the monkey whose turn it is inspect
+// and throw its first item and keeps track of where we are in the round (`played` and `turn`).
+//
+// Stepping and `round()` can be mixed, `round()` finishes the current round. Monkeys in the puzzle
+// never throw to themselves; if one did, stepping would inspect the item again in the same turn.
+
+use crate::{Djungle, NumT, Relief, Worry};
+
+/// What happened in one round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundReport {
+    /// The number of the round, starting at 1.
+    pub round: usize,
+    /// How many items every monkey inspected during the round.
+    pub inspections: Vec<NumT>,
+    /// How many items every monkey holds after the round.
+    pub items: Vec<usize>,
+}
+
+/// A single inspection, as done by `step()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inspection<W: Worry = NumT> {
+    /// The round the inspection happened in, starting at 1.
+    pub round: usize,
+    pub monkey: usize,
+    pub before: W,
+    pub after: W,
+    pub target: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<W: Worry = NumT> {
+    Inspect {
+        round: usize,
+        monkey: usize,
+        before: W,
+        after: W,
+    },
+    Throw {
+        round: usize,
+        from: usize,
+        to: usize,
+        worry: W,
+    },
+    RoundEnd {
+        round: usize,
+    },
+}
+
+impl<W: Worry> Djungle<W> {
+    /// Lets the monkey whose turn it is inspect and throw its first item. If nobody has anything
+    /// left to inspect in this round, the round is over and we get `None`.
+    pub fn step(&mut self) -> Option<Inspection<W>> {
+        while self.turn < self.monkeys.len() && self.monkeys[self.turn].items.is_empty() {
+            self.turn += 1;
+        }
+        if self.turn == self.monkeys.len() {
+            self.turn = 0;
+            self.steps = 0;
+            self.played += 1;
+            return None;
+        }
+
+        let monkey = &mut self.monkeys[self.turn];
+        let mut item = monkey.items.remove(0);
+        let before = item.worry_level.clone();
+        monkey.inspections += 1;
+        self.steps += 1;
+        match self.relief {
+            Relief::DivideByThree => {
+                item.apply_operation(&monkey.operation, &self.mod_factor);
+                item.worry_level.divide_by_three();
+            }
+            Relief::None => {
+                item.worry_level.reduce(&self.mod_factor);
+                item.apply_operation(&monkey.operation, &self.mod_factor);
+            }
+        }
+        let throw = monkey.test.throw(item, &self.mod_factor);
+        let inspection = Inspection {
+            round: self.played + 1,
+            monkey: self.turn,
+            before,
+            after: throw.item.worry_level.clone(),
+            target: throw.target,
+        };
+        self.monkeys[throw.target].catch(throw.item);
+        Some(inspection)
+    }
+
+    pub fn rounds(&mut self) -> Rounds<'_, W> {
+        Rounds { djungle: self }
+    }
+
+    pub fn events(&mut self) -> Events<'_, W> {
+        Events {
+            djungle: self,
+            throw: None,
+        }
+    }
+}
+
+pub struct Rounds<'a, W: Worry> {
+    djungle: &'a mut Djungle<W>,
+}
+
+impl<W: Worry> Iterator for Rounds<'_, W> {
+    type Item = RoundReport;
+
+    fn next(&mut self) -> Option<RoundReport> {
+        let before = self
+            .djungle
+            .monkeys
+            .iter()
+            .map(|m| m.inspections)
+            .collect::<Vec<_>>();
+        self.djungle.round();
+        Some(RoundReport {
+            round: self.djungle.played,
+            inspections: self
+                .djungle
+                .monkeys
+                .iter()
+                .zip(before)
+                .map(|(m, before)| m.inspections - before)
+                .collect(),
+            items: self.djungle.monkeys.iter().map(|m| m.items.len()).collect(),
+        })
+    }
+}
+
+pub struct Events<'a, W: Worry> {
+    djungle: &'a mut Djungle<W>,
+    // every inspection is followed by its throw
+    throw: Option<Event<W>>,
+}
+
+impl<W: Worry> Iterator for Events<'_, W> {
+    type Item = Event<W>;
+
+    fn next(&mut self) -> Option<Event<W>> {
+        if let Some(throw) = self.throw.take() {
+            return Some(throw);
+        }
+        Some(match self.djungle.step() {
+            Some(inspection) => {
+                self.throw = Some(Event::Throw {
+                    round: inspection.round,
+                    from: inspection.monkey,
+                    to: inspection.target,
+                    worry: inspection.after.clone(),
+                });
+                Event::Inspect {
+                    round: inspection.round,
+                    monkey: inspection.monkey,
+                    before: inspection.before,
+                    after: inspection.after,
+                }
+            }
+            None => Event::RoundEnd {
+                round: self.djungle.played,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parsable;
+
+    fn test_djungle(relief: Relief) -> Djungle<u64> {
+        Djungle::parse(include_str!("../test.txt").to_string()).with_relief(relief)
+    }
+
+    #[test]
+    fn test_rounds() {
+        let reports = test_djungle(Relief::None)
+            .rounds()
+            .take(20)
+            .collect::<Vec<_>>();
+        assert_eq!(reports[0].round, 1);
+        assert_eq!(reports[0].inspections, vec![2, 4, 3, 6]);
+        assert_eq!(reports[19].round, 20);
+        // the example after round 20
+        let total = reports.iter().fold(vec![0; 4], |total, report| {
+            total
+                .iter()
+                .zip(report.inspections.iter())
+                .map(|(t, i)| t + i)
+                .collect()
+        });
+        assert_eq!(total, vec![99, 97, 8, 103]);
+        assert_eq!(
+            reports.iter().map(|r| r.items.iter().sum::<usize>()).max(),
+            Some(10)
+        );
+    }
+
+    #[test]
+    fn test_steps_same_as_rounds() {
+        // part 1 worry levels don't fit a u64 for long
+        for (relief, count) in [(Relief::DivideByThree, 20), (Relief::None, 1000)] {
+            let mut stepped = test_djungle(relief);
+            let mut rounds = test_djungle(relief);
+            for _ in 0..count {
+                while stepped.step().is_some() {}
+                rounds.round();
+                assert_eq!(stepped.played, rounds.played);
+                for (s, r) in stepped.monkeys.iter().zip(rounds.monkeys.iter()) {
+                    assert_eq!(s.items, r.items);
+                    assert_eq!(s.inspections, r.inspections);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_finishes_after_steps() {
+        let mut stepped = test_djungle(Relief::DivideByThree);
+        let mut rounds = test_djungle(Relief::DivideByThree);
+        // monkey 0 and half of monkey 1
+        for _ in 0..4 {
+            stepped.step();
+        }
+        assert_eq!(stepped.turn, 1);
+        stepped.round();
+        rounds.round();
+        assert_eq!(stepped.turn, 0);
+        for (s, r) in stepped.monkeys.iter().zip(rounds.monkeys.iter()) {
+            assert_eq!(s.items, r.items);
+        }
+    }
+
+    #[test]
+    fn test_events() {
+        let events = test_djungle(Relief::DivideByThree)
+            .events()
+            .take_while(|e| !matches!(e, Event::RoundEnd { .. }))
+            .collect::<Vec<_>>();
+        // 2 + 4 + 3 + 5 inspections in the first round, each one followed by its throw
+        assert_eq!(events.len(), 2 * 14);
+        assert_eq!(
+            events[0],
+            Event::Inspect {
+                round: 1,
+                monkey: 0,
+                before: 79,
+                after: 500
+            }
+        );
+        assert_eq!(
+            events[1],
+            Event::Throw {
+                round: 1,
+                from: 0,
+                to: 3,
+                worry: 500
+            }
+        );
+    }
+}
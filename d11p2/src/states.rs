@@ -0,0 +1,216 @@
+// Inspection counts from the state space of every item
+//
+// Without relief an item never cares about the other items: where it goes only depends on its own
+// worry level. Reduced modulo the mod factor, an item at the start of a round is fully described by
+// (monkey holding it, worry level), and there are only finitely many of those. So following one
+// item round by round, it has to run into a state it has seen before at some point, and from there
+// on it goes around the same cycle forever. Per item we remember
+// * the states it goes through until the first repeat (transient + cycle),
+// * how often every monkey inspected it after each of those rounds.
+// The inspections after any number of rounds are then the transient part, plus the cycle as often
+// as it fits, plus whatever is left of it.
+//
+// States are worry levels below the mod factor, worked on as u128. If the mod factor or what the
+// operations make of those levels doesn't fit, there's no state space to explore.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{mod_factor, Monkey, NumT};
+
+/// (monkey holding the item, worry level modulo the mod factor) at the start of a round.
+pub type State = (usize, NumT);
+
+/// Where a single item goes, round by round.
+#[derive(Debug, Clone)]
+pub struct Orbit {
+    /// The states at the start of every round, the first `transient` are never seen again, the
+    /// remaining `cycle` ones repeat forever.
+    pub states: Vec<State>,
+    pub transient: usize,
+    pub cycle: usize,
+    // inspections per monkey after 0, 1, ..., states.len() rounds
+    prefix: Vec<Vec<NumT>>,
+}
+
+impl Orbit {
+    pub fn inspections_after(&self, rounds: usize) -> Vec<NumT> {
+        let explored = self.states.len();
+        if rounds <= explored {
+            return self.prefix[rounds].clone();
+        }
+        let cycles = ((rounds - self.transient) / self.cycle) as NumT;
+        let rest = (rounds - self.transient) % self.cycle;
+        let start = &self.prefix[self.transient];
+        self.prefix[explored]
+            .iter()
+            .zip(start)
+            .zip(&self.prefix[self.transient + rest])
+            .map(|((end, start), partial)| partial + (end - start) * cycles)
+            .collect()
+    }
+}
+
+pub struct StateSpace {
+    /// One orbit per starting item, in the order of the input.
+    pub orbits: Vec<Orbit>,
+    pub mod_factor: NumT,
+}
+
+impl StateSpace {
+    /// Follows every item until it repeats a state. Only for part 2, there is no relief. `None` if
+    /// the worry levels don't fit into a u128.
+    pub fn explore(monkeys: &[Monkey]) -> Option<StateSpace> {
+        let mod_factor = mod_factor(monkeys)?;
+        let orbits = monkeys
+            .iter()
+            .enumerate()
+            .flat_map(|(id, monkey)| monkey.items.iter().map(move |item| (id, item.worry_level)))
+            .map(|(id, worry)| Self::orbit(monkeys, mod_factor, (id, worry % mod_factor)))
+            .collect::<Option<_>>()?;
+        Some(StateSpace { orbits, mod_factor })
+    }
+
+    fn orbit(monkeys: &[Monkey], mod_factor: NumT, start: State) -> Option<Orbit> {
+        let mut seen = HashMap::new();
+        let mut states = Vec::new();
+        let mut prefix = vec![vec![0; monkeys.len()]];
+        let mut state = start;
+        while !seen.contains_key(&state) {
+            seen.insert(state, states.len());
+            states.push(state);
+            let mut inspections = prefix.last().unwrap().clone();
+            state = Self::round(monkeys, mod_factor, state, &mut inspections)?;
+            prefix.push(inspections);
+        }
+        let transient = seen[&state];
+        Some(Orbit {
+            cycle: states.len() - transient,
+            transient,
+            states,
+            prefix,
+        })
+    }
+
+    // one round for a single item: it keeps going as long as it is thrown to monkeys that still
+    // get their turn in this round
+    fn round(
+        monkeys: &[Monkey],
+        mod_factor: NumT,
+        (mut monkey, mut worry): State,
+        inspections: &mut [NumT],
+    ) -> Option<State> {
+        loop {
+            inspections[monkey] += 1;
+            worry = monkeys[monkey].operation.worst_case(worry)? % mod_factor;
+            let test = &monkeys[monkey].test;
+            let target = if worry % test.divisor == 0 {
+                test.true_target
+            } else {
+                test.false_target
+            };
+            if target <= monkey {
+                return Some((target, worry));
+            }
+            monkey = target;
+        }
+    }
+
+    /// The number of different states any item can be in.
+    pub fn size(&self) -> usize {
+        self.orbits
+            .iter()
+            .flat_map(|o| o.states.iter())
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// How often every monkey inspects an item after the given number of rounds.
+    pub fn inspections_after(&self, rounds: usize) -> Vec<NumT> {
+        let mut inspections = vec![0; self.monkey_count()];
+        for orbit in self.orbits.iter() {
+            for (total, count) in inspections.iter_mut().zip(orbit.inspections_after(rounds)) {
+                *total += count;
+            }
+        }
+        inspections
+    }
+
+    pub fn monkey_business_after(&self, rounds: usize) -> NumT {
+        let mut inspections = self.inspections_after(rounds);
+        inspections.sort_unstable();
+        inspections.iter().rev().take(2).product()
+    }
+
+    fn monkey_count(&self) -> usize {
+        self.orbits.first().map_or(0, |o| o.prefix[0].len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Djungle, Parsable};
+
+    #[test]
+    fn test_same_as_brute_force() {
+        let input = include_str!("../test.txt");
+        let space = StateSpace::explore(&Vec::<Monkey>::parse(input.to_string())).unwrap();
+        let mut djungle: Djungle<u64> = Djungle::parse(input.to_string());
+        for rounds in 0..=10000 {
+            let inspections = djungle
+                .monkeys
+                .iter()
+                .map(|m| m.inspections)
+                .collect::<Vec<_>>();
+            if rounds % 500 == 0 || rounds < 50 {
+                assert_eq!(space.inspections_after(rounds), inspections);
+            }
+            djungle.round();
+        }
+        assert_eq!(space.monkey_business_after(10000), 2713310158);
+    }
+
+    #[test]
+    fn test_orbits_repeat() {
+        let monkeys = Vec::<Monkey>::parse(include_str!("../test.txt").to_string());
+        let space = StateSpace::explore(&monkeys).unwrap();
+        assert_eq!(space.orbits.len(), 10);
+        for orbit in space.orbits.iter() {
+            assert!(orbit.cycle > 0);
+            assert_eq!(orbit.states.len(), orbit.transient + orbit.cycle);
+            // one more round after the last explored state is back at the start of the cycle
+            let mut inspections = vec![0; 4];
+            let next = StateSpace::round(
+                &monkeys,
+                space.mod_factor,
+                *orbit.states.last().unwrap(),
+                &mut inspections,
+            );
+            assert_eq!(next, Some(orbit.states[orbit.transient]));
+        }
+    }
+
+    #[test]
+    fn test_input() {
+        let space = StateSpace::explore(&Vec::<Monkey>::parse(
+            include_str!("../input.txt").to_string(),
+        ))
+        .unwrap();
+        assert_eq!(space.monkey_business_after(10000), 51382025916);
+        // way past anything we could simulate
+        assert!(space.monkey_business_after(1_000_000_000) > 51382025916);
+    }
+
+    #[test]
+    fn test_too_big() {
+        let input = include_str!("../test.txt");
+        // squaring a level below a mod factor of 2^66 doesn't fit into a u128
+        let big = input
+            .replace("divisible by 23", "divisible by 36893488147419103232")
+            .replace("divisible by 19", "divisible by 2");
+        assert!(StateSpace::explore(&Vec::<Monkey>::parse(big.clone())).is_none());
+        // and neither does a mod factor of 2^130
+        let bigger = big.replace("divisible by 13", "divisible by 36893488147419103232");
+        assert!(StateSpace::explore(&Vec::<Monkey>::parse(bigger)).is_none());
+    }
+}
@@ -0,0 +1,396 @@
+// Recording every throw, and checking a run against a recording
+//
+// `round_logged` plays a round with `round_observed`, so exactly like `round` does, and writes down
+// every throw: round, from, to, worry level before and after the inspection. Replaying a log
+// plays the same number of rounds on a fresh jungle and compares throw by throw, so a change to the
+// inspecting or throwing code that changes anything at all shows up as the first throw that's off.
+//
+// The file format is small on purpose, 10000 rounds of a real input are about a million throws:
+//   "DJTL", version byte, relief byte (0 = none, 1 = divide by three), rounds, number of throws
+//   then per throw: rounds since the previous throw, from, to, before, after
+// every number after the version is an unsigned LEB128 varint.
+
+use std::fmt;
+
+use crate::{Djungle, Monkey, NumT, Observer, Relief, Throw, Worry};
+
+const MAGIC: &[u8; 4] = b"DJTL";
+const VERSION: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThrowRecord {
+    /// Starting at 1.
+    pub round: usize,
+    pub from: usize,
+    pub to: usize,
+    pub before: NumT,
+    pub after: NumT,
+}
+
+impl fmt::Display for ThrowRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "round {}: monkey {} -> {}, worry {} -> {}",
+            self.round, self.from, self.to, self.before, self.after
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThrowLog {
+    pub relief: Relief,
+    pub rounds: usize,
+    pub throws: Vec<ThrowRecord>,
+}
+
+/// The first throw a replay didn't reproduce.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// Which throw of the log it is.
+    pub index: usize,
+    pub expected: Option<ThrowRecord>,
+    pub actual: Option<ThrowRecord>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |r: &Option<ThrowRecord>| match r {
+            Some(r) => r.to_string(),
+            None => "nothing".to_string(),
+        };
+        write!(
+            f,
+            "throw {}: expected {}, got {}",
+            self.index,
+            show(&self.expected),
+            show(&self.actual)
+        )
+    }
+}
+
+// writes down the throws of one round
+struct Logger<'a> {
+    log: &'a mut ThrowLog,
+    round: usize,
+    // the worry levels of the monkey whose turn it is, before inspecting them, the first one last
+    before: Vec<NumT>,
+}
+
+impl<W: Worry + Into<NumT>> Observer<W> for Logger<'_> {
+    fn turn(&mut self, monkey: &Monkey<W>) {
+        self.before = monkey
+            .items
+            .iter()
+            .rev()
+            .map(|i| i.worry_level.clone().into())
+            .collect();
+    }
+
+    fn throw(&mut self, from: usize, throw: &Throw<W>) {
+        self.log.throws.push(ThrowRecord {
+            round: self.round,
+            from,
+            to: throw.target,
+            before: self.before.pop().expect("a throw for every item"),
+            after: throw.item.worry_level.clone().into(),
+        });
+    }
+}
+
+impl<W: Worry + Into<NumT>> Djungle<W> {
+    /// Same as `round`, writing every throw into the log.
+    pub fn round_logged(&mut self, log: &mut ThrowLog) {
+        let round = self.played + 1;
+        self.round_observed(&mut Logger {
+            log,
+            round,
+            before: Vec::new(),
+        });
+        log.rounds += 1;
+    }
+}
+
+impl ThrowLog {
+    pub fn new(relief: Relief) -> Self {
+        ThrowLog {
+            relief,
+            rounds: 0,
+            throws: Vec::new(),
+        }
+    }
+
+    /// Plays the given number of rounds and records them.
+    pub fn record<W: Worry + Into<NumT>>(djungle: &mut Djungle<W>, rounds: usize) -> Self {
+        let mut log = ThrowLog::new(djungle.relief());
+        for _ in 0..rounds {
+            djungle.round_logged(&mut log);
+        }
+        log
+    }
+
+    /// Plays the rounds of the log on a jungle that hasn't played yet, and compares.
+    pub fn replay<W: Worry + Into<NumT>>(&self, djungle: Djungle<W>) -> Result<(), Box<Mismatch>> {
+        let mut djungle = djungle.with_relief(self.relief);
+        let mut replayed = ThrowLog::new(self.relief);
+        let mut compared = 0;
+        for _ in 0..self.rounds {
+            djungle.round_logged(&mut replayed);
+            // compare as we go, so a diverging run doesn't go on for all the rounds
+            for index in compared..replayed.throws.len() {
+                if self.throws.get(index) != Some(&replayed.throws[index]) {
+                    return Err(Box::new(Mismatch {
+                        index,
+                        expected: self.throws.get(index).copied(),
+                        actual: Some(replayed.throws[index]),
+                    }));
+                }
+            }
+            compared = replayed.throws.len();
+        }
+        if compared < self.throws.len() {
+            return Err(Box::new(Mismatch {
+                index: compared,
+                expected: Some(self.throws[compared]),
+                actual: None,
+            }));
+        }
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.push(match self.relief {
+            Relief::None => 0,
+            Relief::DivideByThree => 1,
+        });
+        write_varint(&mut bytes, self.rounds as NumT);
+        write_varint(&mut bytes, self.throws.len() as NumT);
+        let mut round = 0;
+        for throw in self.throws.iter() {
+            write_varint(&mut bytes, (throw.round - round) as NumT);
+            round = throw.round;
+            write_varint(&mut bytes, throw.from as NumT);
+            write_varint(&mut bytes, throw.to as NumT);
+            write_varint(&mut bytes, throw.before);
+            write_varint(&mut bytes, throw.after);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let Some(rest) = bytes.strip_prefix(MAGIC) else {
+            return Err("not a throw log".to_string());
+        };
+        let mut reader = Reader { bytes: rest };
+        match reader.byte()? {
+            VERSION => {}
+            version => return Err(format!("unsupported throw log version {}", version)),
+        }
+        let relief = match reader.byte()? {
+            0 => Relief::None,
+            1 => Relief::DivideByThree,
+            relief => return Err(format!("unknown relief {}", relief)),
+        };
+        let rounds = reader.usize()?;
+        let count = reader.usize()?;
+        let mut throws = Vec::new();
+        let mut round: usize = 0;
+        for _ in 0..count {
+            round = round
+                .checked_add(reader.usize()?)
+                .ok_or_else(|| "round is too large".to_string())?;
+            throws.push(ThrowRecord {
+                round,
+                from: reader.usize()?,
+                to: reader.usize()?,
+                before: reader.varint()?,
+                after: reader.varint()?,
+            });
+        }
+        if !reader.bytes.is_empty() {
+            return Err(format!(
+                "{} bytes left after the last throw",
+                reader.bytes.len()
+            ));
+        }
+        Ok(ThrowLog {
+            relief,
+            rounds,
+            throws,
+        })
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: NumT) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let (byte, rest) = self
+            .bytes
+            .split_first()
+            .ok_or_else(|| "throw log ends too early".to_string())?;
+        self.bytes = rest;
+        Ok(*byte)
+    }
+
+    fn varint(&mut self) -> Result<NumT, String> {
+        let mut value: NumT = 0;
+        for shift in (0..NumT::BITS).step_by(7) {
+            let byte = self.byte()?;
+            let bits = (byte & 0x7f) as NumT;
+            // the last byte only has room for the two top bits
+            if bits > NumT::MAX >> shift {
+                return Err("varint is too large".to_string());
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("varint is too long".to_string())
+    }
+
+    fn usize(&mut self) -> Result<usize, String> {
+        let value = self.varint()?;
+        usize::try_from(value).map_err(|_| format!("{} is too large", value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parsable;
+
+    fn test_djungle(relief: Relief) -> Djungle<u64> {
+        Djungle::parse(include_str!("../test.txt").to_string()).with_relief(relief)
+    }
+
+    #[test]
+    fn test_logged_round_is_a_round() {
+        let mut logged = test_djungle(Relief::None);
+        let mut plain = test_djungle(Relief::None);
+        let log = ThrowLog::record(&mut logged, 1000);
+        for _ in 0..1000 {
+            plain.round();
+        }
+        assert_eq!(logged.monkey_business(), plain.monkey_business());
+        let inspections = plain.monkeys.iter().map(|m| m.inspections).sum::<NumT>();
+        assert_eq!(log.throws.len() as NumT, inspections);
+        assert_eq!(
+            log.throws[0],
+            ThrowRecord {
+                round: 1,
+                from: 0,
+                to: 3,
+                before: 79,
+                after: 1501
+            }
+        );
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        for relief in [Relief::DivideByThree, Relief::None] {
+            let log = ThrowLog::record(&mut test_djungle(relief), 20);
+            let bytes = log.to_bytes();
+            assert_eq!(ThrowLog::from_bytes(&bytes), Ok(log));
+        }
+        let mut big = ThrowLog::new(Relief::None);
+        big.rounds = 1;
+        big.throws.push(ThrowRecord {
+            round: 1,
+            from: 7,
+            to: 0,
+            before: NumT::MAX,
+            after: 0,
+        });
+        assert_eq!(ThrowLog::from_bytes(&big.to_bytes()), Ok(big));
+    }
+
+    #[test]
+    fn test_broken_bytes() {
+        let bytes = ThrowLog::record(&mut test_djungle(Relief::None), 2).to_bytes();
+        assert!(ThrowLog::from_bytes(b"nope").is_err());
+        assert!(ThrowLog::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut newer = bytes.clone();
+        newer[4] = VERSION + 1;
+        assert_eq!(
+            ThrowLog::from_bytes(&newer),
+            Err(format!("unsupported throw log version {}", VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn test_large_varints() {
+        let read = |bytes: &[u8]| Reader { bytes }.varint();
+        let mut max = Vec::new();
+        write_varint(&mut max, NumT::MAX);
+        assert_eq!(max.len(), 19);
+        assert_eq!(read(&max), Ok(NumT::MAX));
+        // bits beyond the 128th, and a 20th byte
+        let mut above = max.clone();
+        above[18] = 0x04;
+        assert_eq!(read(&above), Err("varint is too large".to_string()));
+        let mut longer = max.clone();
+        longer[18] |= 0x80;
+        longer.push(0);
+        assert_eq!(read(&longer), Err("varint is too long".to_string()));
+
+        // rounds since the previous throw that add up beyond a usize
+        let mut log = ThrowLog::new(Relief::None);
+        log.rounds = 1;
+        let throw = ThrowRecord {
+            round: usize::MAX,
+            from: 0,
+            to: 1,
+            before: 1,
+            after: 2,
+        };
+        log.throws = vec![throw, throw];
+        let mut bytes = log.to_bytes();
+        assert!(ThrowLog::from_bytes(&bytes).is_ok());
+        // the second throw comes usize::MAX rounds after the first, instead of 0
+        let second = bytes.len() - 5;
+        assert_eq!(bytes[second], 0);
+        bytes.splice(second..=second, max[..10].iter().copied());
+        bytes[second + 9] = 0x01;
+        assert_eq!(
+            ThrowLog::from_bytes(&bytes),
+            Err("round is too large".to_string())
+        );
+    }
+
+    #[test]
+    fn test_replay() {
+        let log = ThrowLog::record(&mut test_djungle(Relief::None), 100);
+        // the relief comes from the log
+        assert_eq!(log.replay(test_djungle(Relief::DivideByThree)), Ok(()));
+
+        let mut tampered = log.clone();
+        tampered.throws[500].to = (tampered.throws[500].to + 1) % 4;
+        let mismatch = tampered.replay(test_djungle(Relief::None)).unwrap_err();
+        assert_eq!(mismatch.index, 500);
+        assert_eq!(mismatch.actual, Some(log.throws[500]));
+
+        let mut longer = log.clone();
+        longer.throws.push(log.throws[0]);
+        let mismatch = longer.replay(test_djungle(Relief::None)).unwrap_err();
+        assert_eq!((mismatch.index, mismatch.actual), (log.throws.len(), None));
+    }
+}
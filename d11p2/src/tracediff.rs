@@ -0,0 +1,183 @@
+// Finding where two runs stop agreeing
+//
+// Two runs of the same jungle throw the same items in the same order, so their throw logs (see
+// `throwlog.rs`) can simply be compared one throw after the other. The first throw that differs
+// (a different monkey, target or worry level, or one run simply ending) is where something went
+// wrong; the monkey business only tells us that something did. Worry levels can be left out of the
+// comparison for runs that store them differently.
+
+use std::fmt;
+
+use crate::{NumT, ThrowRecord};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// Which throw it is, counting from 0.
+    pub index: usize,
+    pub left: Option<ThrowRecord>,
+    pub right: Option<ThrowRecord>,
+    /// What's different: "round", "monkey", "target", "worry before", "worry after" or "length".
+    pub differences: Vec<&'static str>,
+    /// The throws both runs agreed on right before.
+    pub before: Vec<ThrowRecord>,
+    /// What each run did afterwards.
+    pub left_after: Vec<ThrowRecord>,
+    pub right_after: Vec<ThrowRecord>,
+    /// Every monkey's inspections up to the divergence, the same for both runs.
+    pub inspections: Vec<NumT>,
+}
+
+/// Compares two runs throw by throw, showing `context` throws around the first difference.
+pub fn first_divergence(
+    left: &[ThrowRecord],
+    right: &[ThrowRecord],
+    compare_worry: bool,
+    context: usize,
+) -> Option<Divergence> {
+    let differences = |l: Option<&ThrowRecord>, r: Option<&ThrowRecord>| {
+        let (Some(l), Some(r)) = (l, r) else {
+            return vec!["length"];
+        };
+        let mut differences = vec![];
+        if l.round != r.round {
+            differences.push("round");
+        }
+        if l.from != r.from {
+            differences.push("monkey");
+        }
+        if l.to != r.to {
+            differences.push("target");
+        }
+        if compare_worry && l.before != r.before {
+            differences.push("worry before");
+        }
+        if compare_worry && l.after != r.after {
+            differences.push("worry after");
+        }
+        differences
+    };
+
+    let index = (0..left.len().max(right.len()))
+        .find(|i| !differences(left.get(*i), right.get(*i)).is_empty())?;
+
+    let monkeys = left
+        .iter()
+        .chain(right.iter())
+        .map(|t| t.from.max(t.to) + 1)
+        .max()
+        .unwrap_or(0);
+    let mut inspections = vec![0; monkeys];
+    for throw in left[..index].iter() {
+        inspections[throw.from] += 1;
+    }
+
+    let after = |run: &[ThrowRecord]| run.iter().skip(index + 1).take(context).copied().collect();
+    Some(Divergence {
+        index,
+        left: left.get(index).copied(),
+        right: right.get(index).copied(),
+        differences: differences(left.get(index), right.get(index)),
+        before: left[index.saturating_sub(context)..index].to_vec(),
+        left_after: after(left),
+        right_after: after(right),
+        inspections,
+    })
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |r: &Option<ThrowRecord>| match r {
+            Some(r) => r.to_string(),
+            None => "nothing, the run is over".to_string(),
+        };
+        writeln!(
+            f,
+            "First difference at throw {} ({})",
+            self.index,
+            self.differences.join(", ")
+        )?;
+        writeln!(f, "Inspections until then: {:?}", self.inspections)?;
+        writeln!(f, "Both runs before:")?;
+        for throw in self.before.iter() {
+            writeln!(f, "    {}", throw)?;
+        }
+        writeln!(f, "Left:  {}", show(&self.left))?;
+        for throw in self.left_after.iter() {
+            writeln!(f, "    {}", throw)?;
+        }
+        writeln!(f, "Right: {}", show(&self.right))?;
+        for throw in self.right_after.iter() {
+            writeln!(f, "    {}", throw)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Djungle, Overflowing, Parsable, Relief, ThrowLog, Worry};
+
+    fn record<W: Worry + Into<NumT>>(relief: Relief, rounds: usize) -> ThrowLog {
+        let mut djungle: Djungle<W> =
+            Djungle::parse(include_str!("../test.txt").to_string()).with_relief(relief);
+        ThrowLog::record(&mut djungle, rounds)
+    }
+
+    #[test]
+    fn test_same_runs() {
+        let u64_run = record::<u64>(Relief::DivideByThree, 20);
+        let u128_run = record::<u128>(Relief::DivideByThree, 20);
+        assert_eq!(
+            first_divergence(&u64_run.throws, &u128_run.throws, true, 3),
+            None
+        );
+    }
+
+    #[test]
+    fn test_changed_target() {
+        let left = record::<u64>(Relief::None, 10).throws;
+        let mut right = left.clone();
+        right[100].to = (right[100].to + 1) % 4;
+        let divergence = first_divergence(&left, &right, true, 3).unwrap();
+        assert_eq!(divergence.index, 100);
+        assert_eq!(divergence.differences, vec!["target"]);
+        assert_eq!(divergence.before, left[97..100].to_vec());
+        assert_eq!(divergence.left_after, left[101..104].to_vec());
+        assert_eq!(divergence.inspections.iter().sum::<NumT>(), 100);
+        // the worry levels alone don't matter when they're not compared
+        right[100] = left[100];
+        right[50].after += 1;
+        assert_eq!(first_divergence(&left, &right, false, 3), None);
+        assert_eq!(
+            first_divergence(&left, &right, true, 3)
+                .unwrap()
+                .differences,
+            vec!["worry after"]
+        );
+    }
+
+    #[test]
+    fn test_shorter_run() {
+        let left = record::<u64>(Relief::None, 10).throws;
+        let right = left[..42].to_vec();
+        let divergence = first_divergence(&left, &right, true, 2).unwrap();
+        assert_eq!(divergence.index, 42);
+        assert_eq!(divergence.right, None);
+        assert_eq!(divergence.differences, vec!["length"]);
+        assert!(divergence.to_string().contains("the run is over"));
+    }
+
+    #[test]
+    fn test_overflowing_u32() {
+        // part 2 on the example doesn't fit a u32 (96576 * 96576), the first throw that wrapped
+        // around is where the runs part
+        let left = record::<u64>(Relief::None, 30).throws;
+        let right = record::<Overflowing<u32>>(Relief::None, 30).throws;
+        let divergence = first_divergence(&left, &right, true, 0).unwrap();
+        assert_eq!(divergence.differences, vec!["worry after"]);
+        let (left, right) = (divergence.left.unwrap(), divergence.right.unwrap());
+        assert!(left.after > u32::MAX as NumT);
+        assert_eq!(right.after, left.after as u32 as NumT);
+    }
+}
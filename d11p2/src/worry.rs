@@ -0,0 +1,136 @@
+// How a worry level is stored.
+//
+// The engine only ever adds, multiplies, reduces and tests worry levels, so anything that can do
+// those four things can be used as a worry level. Plain integers keep one number that is reduced
+// modulo the product of all divisors (the mod factor), other representations (see `residue.rs`)
+// keep whatever they like as long as the divisibility tests come out the same.
+
+use std::fmt::Debug;
+
+use num_bigint::BigUint;
+
+use crate::NumT;
+
+pub trait Worry: Clone + Debug + PartialEq {
+    /// Whatever the representation needs to know about the jungle to keep worry levels small,
+    /// built once from the divisors of all tests.
+    type Modulus: Clone + Debug;
+
+    /// Says why the representation can't work with these divisors instead of panicking.
+    fn modulus(divisors: &[NumT]) -> Result<Self::Modulus, String>;
+    /// Turns a starting worry level from the input into this representation, or says why it
+    /// doesn't fit.
+    fn try_lift(level: NumT, modulus: &Self::Modulus) -> Result<Self, String>;
+
+    fn lift(level: NumT, modulus: &Self::Modulus) -> Self {
+        Self::try_lift(level, modulus).unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Keeps the worry level from growing out of bounds without changing any test result.
+    fn reduce(&mut self, modulus: &Self::Modulus);
+    /// Part 1 relief. Only representations that know the actual worry level can do this.
+    fn divide_by_three(&mut self);
+    /// Says why `divide_by_three` doesn't work, so `Djungle::try_with_relief` can turn the
+    /// representation away before any round is played.
+    fn can_divide_by_three() -> Result<(), String> {
+        Ok(())
+    }
+
+    fn add(&self, right: &Self, modulus: &Self::Modulus) -> Self;
+    fn mul(&self, right: &Self, modulus: &Self::Modulus) -> Self;
+    fn add_constant(&self, right: NumT, modulus: &Self::Modulus) -> Self;
+    fn mul_constant(&self, right: NumT, modulus: &Self::Modulus) -> Self;
+
+    fn is_divisible_by(&self, divisor: NumT, modulus: &Self::Modulus) -> bool;
+}
+
+macro_rules! impl_worry_for_int {
+    ($($t:ty),*) => {$(
+        // the modulus of a plain integer is the mod factor itself
+        impl Worry for $t {
+            type Modulus = $t;
+
+            fn modulus(divisors: &[NumT]) -> Result<$t, String> {
+                divisors
+                    .iter()
+                    .try_fold(1 as $t, |acc, d| {
+                        <$t>::try_from(*d).ok().and_then(|d| acc.checked_mul(d))
+                    })
+                    .ok_or_else(|| format!("mod factor does not fit into a {}", stringify!($t)))
+            }
+
+            fn try_lift(level: NumT, _: &$t) -> Result<$t, String> {
+                <$t>::try_from(level)
+                    .map_err(|_| format!("worry level {} does not fit into a {}", level, stringify!($t)))
+            }
+
+            fn reduce(&mut self, modulus: &$t) {
+                *self %= modulus;
+            }
+
+            fn divide_by_three(&mut self) {
+                *self /= 3;
+            }
+
+            fn add(&self, right: &$t, _: &$t) -> $t {
+                self + right
+            }
+
+            fn mul(&self, right: &$t, _: &$t) -> $t {
+                self * right
+            }
+
+            fn add_constant(&self, right: NumT, _: &$t) -> $t {
+                self + right as $t
+            }
+
+            fn mul_constant(&self, right: NumT, _: &$t) -> $t {
+                self * right as $t
+            }
+
+            fn is_divisible_by(&self, divisor: NumT, _: &$t) -> bool {
+                self.is_multiple_of(divisor as $t)
+            }
+        }
+    )*};
+}
+
+impl_worry_for_int!(u32, u64, u128);
+
+// unreduced worry levels (the shadow in invariants.rs), there is nothing to keep them small
+impl Worry for BigUint {
+    type Modulus = ();
+
+    fn modulus(_: &[NumT]) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn try_lift(level: NumT, _: &()) -> Result<Self, String> {
+        Ok(BigUint::from(level))
+    }
+
+    fn reduce(&mut self, _: &()) {}
+
+    fn divide_by_three(&mut self) {
+        *self /= 3u32;
+    }
+
+    fn add(&self, right: &Self, _: &()) -> Self {
+        self + right
+    }
+
+    fn mul(&self, right: &Self, _: &()) -> Self {
+        self * right
+    }
+
+    fn add_constant(&self, right: NumT, _: &()) -> Self {
+        self + right
+    }
+
+    fn mul_constant(&self, right: NumT, _: &()) -> Self {
+        self * right
+    }
+
+    fn is_divisible_by(&self, divisor: NumT, _: &()) -> bool {
+        self % divisor == BigUint::from(0u32)
+    }
+}
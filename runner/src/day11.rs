@@ -0,0 +1,163 @@
+// Day 11: Monkey in the Middle, both parts with the djungle library of d11p2
+//
+// Part 1 takes the narrowest integer the overflow horizon allows and that can hold the jungle (it
+// never reduces, but the mod factor is still computed). The horizon is pessimistic, so when it
+// doesn't even allow a u128 we play with levels that notice when they overflow and only give up if
+// one really did. Part 2 takes whichever backend `Backend::select` picks.
+
+use djungle::{
+    overflow_horizon, Backend, Djungle, Horizon, Monkey, NumT, Overflowing, Parsable, Relief,
+    Simulation, Worry,
+};
+
+use crate::Solution;
+
+pub struct Day11;
+
+#[derive(Clone, Copy)]
+struct Play {
+    relief: Relief,
+    rounds: usize,
+}
+
+impl Simulation for Play {
+    type Output = Result<NumT, String>;
+
+    fn run<W: Worry>(self, djungle: Djungle<W>) -> Result<NumT, String> {
+        let mut djungle = djungle.try_with_relief(self.relief)?;
+        for _ in 0..self.rounds {
+            djungle.round();
+        }
+        Ok(djungle.monkey_business())
+    }
+}
+
+impl Solution for Day11 {
+    type Parsed = Vec<Monkey>;
+
+    fn parse(input: &str) -> Result<Vec<Monkey>, String> {
+        Vec::<Monkey>::try_parse(input)
+    }
+
+    fn part1(monkeys: &Vec<Monkey>) -> Result<String, String> {
+        let play = Play {
+            relief: Relief::DivideByThree,
+            rounds: 20,
+        };
+        let fits = |bits| {
+            !matches!(
+                overflow_horizon(monkeys, play.relief, bits, play.rounds),
+                Horizon::Overflow(_)
+            )
+        };
+        for (backend, bits) in [(Backend::U64, u64::BITS), (Backend::U128, u128::BITS)] {
+            if !fits(bits) {
+                continue;
+            }
+            // otherwise the backend can't hold the mod factor or an item, a wider one might
+            if let Ok(business) = backend.dispatch(monkeys.clone(), play) {
+                return Ok(business?.to_string());
+            }
+        }
+        let mut djungle = Djungle::<Overflowing<u128>>::try_lift(monkeys.clone())?
+            .try_with_relief(play.relief)?;
+        for round in 1..=play.rounds {
+            djungle.round();
+            if djungle.overflowed() {
+                return Err(format!("worry levels overflow a u128 in round {}", round));
+            }
+        }
+        Ok(djungle.monkey_business().to_string())
+    }
+
+    fn part2(monkeys: &Vec<Monkey>) -> Result<String, String> {
+        let play = Play {
+            relief: Relief::None,
+            rounds: 10000,
+        };
+        Ok(Backend::select(monkeys)?
+            .dispatch(monkeys.clone(), play)??
+            .to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example() {
+        let monkeys = Day11::parse(include_str!("../../d11/test.txt")).unwrap();
+        assert_eq!(Day11::part1(&monkeys), Ok("10605".to_string()));
+        assert_eq!(Day11::part2(&monkeys), Ok("2713310158".to_string()));
+    }
+
+    #[test]
+    fn test_input() {
+        let monkeys = Day11::parse(include_str!("../../d11/input.txt")).unwrap();
+        assert_eq!(Day11::part1(&monkeys), Ok("151312".to_string()));
+        assert_eq!(Day11::part2(&monkeys), Ok("51382025916".to_string()));
+    }
+
+    #[test]
+    fn test_part1_overflows() {
+        // squaring a level of 2^64 overflows a u128 in the very first round
+        let input = "Monkey 0:
+  Starting items: 18446744073709551616
+  Operation: new = old * old
+  Test: divisible by 2
+    If true: throw to monkey 1
+    If false: throw to monkey 1
+
+Monkey 1:
+  Starting items: 1
+  Operation: new = old + 1
+  Test: divisible by 3
+    If true: throw to monkey 0
+    If false: throw to monkey 0
+";
+        let monkeys = Day11::parse(input).unwrap();
+        assert_eq!(
+            Day11::part1(&monkeys),
+            Err("worry levels overflow a u128 in round 1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_part1_big_divisors() {
+        // a mod factor of 2^80 doesn't fit a u64, even though the worry levels would
+        let input = include_str!("../../d11/test.txt")
+            .replace("divisible by 23", "divisible by 1099511627776")
+            .replace("divisible by 19", "divisible by 1099511627776");
+        let monkeys = Day11::parse(&input).unwrap();
+        let mut djungle = Djungle::<u128>::lift(monkeys.clone()).with_relief(Relief::DivideByThree);
+        for _ in 0..20 {
+            djungle.round();
+        }
+        assert_eq!(
+            Day11::part1(&monkeys),
+            Ok(djungle.monkey_business().to_string())
+        );
+        // 2^160 doesn't fit anything
+        let input = input
+            .replace("divisible by 13", "divisible by 1099511627776")
+            .replace("divisible by 17", "divisible by 1099511627776");
+        let monkeys = Day11::parse(&input).unwrap();
+        assert_eq!(
+            Day11::part1(&monkeys),
+            Err("mod factor does not fit into a u128".to_string())
+        );
+    }
+
+    #[test]
+    fn test_part2_without_backend() {
+        // 2^70 neither fits a residue nor makes a mod factor that fits a u128
+        let input = include_str!("../../d11/test.txt")
+            .replace("divisible by 23", "divisible by 1180591620717411303424")
+            .replace("divisible by 19", "divisible by 1180591620717411303424");
+        let monkeys = Day11::parse(&input).unwrap();
+        assert!(Day11::part2(&monkeys)
+            .unwrap_err()
+            .contains("does not fit into a u32"));
+    }
+}
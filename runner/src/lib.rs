@@ -0,0 +1,162 @@
+// One way to run every day
+//
+// A day parses its input once and answers both parts from what it parsed, see `Solution`. `DAYS`
+// is the registry, every entry knows where the day keeps its input (relative to the workspace)
+// and how to run it. Parsing and each part are timed separately, so a slow part 2 doesn't hide
+// behind the parser or the other way around. A part that can't find its answer fails the run.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+mod day11;
+
+pub trait Solution {
+    type Parsed;
+
+    fn parse(input: &str) -> Result<Self::Parsed, String>;
+    fn part1(parsed: &Self::Parsed) -> Result<String, String>;
+    fn part2(parsed: &Self::Parsed) -> Result<String, String>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const BOTH: [Part; 2] = [Part::One, Part::Two];
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            _ => Err(format!("unknown part: {}, 1 or 2", s)),
+        }
+    }
+}
+
+pub struct Day {
+    pub day: u32,
+    /// Relative to the workspace.
+    pub input: &'static str,
+    run: fn(&str, &[Part]) -> Result<Report, String>,
+}
+
+impl Day {
+    const fn new<S: Solution>(day: u32, input: &'static str) -> Self {
+        Day {
+            day,
+            input,
+            run: run::<S>,
+        }
+    }
+
+    pub fn run(&self, input: &str, parts: &[Part]) -> Result<Report, String> {
+        (self.run)(input, parts)
+    }
+}
+
+pub const DAYS: &[Day] = &[Day::new::<day11::Day11>(11, "d11/input.txt")];
+
+pub fn day(day: u32) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.day == day)
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub parse: Duration,
+    pub answers: Vec<Answer>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Answer {
+    pub part: Part,
+    pub answer: String,
+    pub time: Duration,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "parsed in {:?}", self.parse)?;
+        for answer in self.answers.iter() {
+            writeln!(
+                f,
+                "part {}: {} ({:?})",
+                answer.part, answer.answer, answer.time
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn run<S: Solution>(input: &str, parts: &[Part]) -> Result<Report, String> {
+    let start = Instant::now();
+    let parsed = S::parse(input)?;
+    let parse = start.elapsed();
+    let answers = parts
+        .iter()
+        .map(|part| {
+            let start = Instant::now();
+            let answer = match part {
+                Part::One => S::part1(&parsed),
+                Part::Two => S::part2(&parsed),
+            }
+            .map_err(|e| format!("part {}: {}", part, e))?;
+            Ok(Answer {
+                part: *part,
+                answer,
+                time: start.elapsed(),
+            })
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(Report { parse, answers })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        assert_eq!(day(11).map(|d| d.input), Some("d11/input.txt"));
+        assert!(day(12).is_none());
+        let mut days = DAYS.iter().map(|d| d.day).collect::<Vec<_>>();
+        days.sort();
+        days.dedup();
+        assert_eq!(days.len(), DAYS.len());
+    }
+
+    #[test]
+    fn test_parts() {
+        assert_eq!("2".parse::<Part>(), Ok(Part::Two));
+        assert!("3".parse::<Part>().is_err());
+        for part in Part::BOTH {
+            assert_eq!(part.to_string().parse::<Part>(), Ok(part));
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        let example = include_str!("../../d11/test.txt");
+        let report = day(11).unwrap().run(example, &[Part::Two]).unwrap();
+        assert_eq!(report.answers.len(), 1);
+        assert_eq!(report.answers[0].answer, "2713310158");
+        assert!(report.to_string().contains("part 2: 2713310158"));
+        assert!(day(11).unwrap().run("Monkey 0:", &Part::BOTH).is_err());
+    }
+}
@@ -0,0 +1,52 @@
+// Run a day of the calendar, or just one part of it
+//
+// cargo run --release --bin aoc -- run 11
+// cargo run --release --bin aoc -- run 11 2
+
+use std::path::Path;
+
+use runner::{day, Part, DAYS};
+
+fn usage() -> ! {
+    eprintln!("usage: aoc run <day> [part]");
+    eprintln!(
+        "days: {}",
+        DAYS.iter()
+            .map(|d| d.day.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    std::process::exit(2);
+}
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(|a| a.as_str()).collect::<Vec<_>>();
+    let (number, parts) = match args.as_slice() {
+        ["run", number] => (number, Part::BOTH.to_vec()),
+        ["run", number, part] => (
+            number,
+            vec![part.parse::<Part>().unwrap_or_else(|_| usage())],
+        ),
+        _ => usage(),
+    };
+    let Some(day) = number.parse::<u32>().ok().and_then(day) else {
+        eprintln!("no solution for day {}", number);
+        usage();
+    };
+
+    // the workspace is one up from this crate
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(day.input);
+    let input =
+        std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    println!("Day {}", day.day);
+    match day.run(&input, &parts) {
+        Ok(report) => print!("{}", report),
+        Err(e) => {
+            eprintln!("{}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    }
+}